@@ -1,4 +1,4 @@
-use rustc_version::{version_meta, Channel};
+use rustc_version::{version_meta, Channel, Version};
 
 fn main() {
     // Set cfg flags depending on release channel
@@ -8,5 +8,10 @@ fn main() {
         Channel::Nightly => "CHANNEL_NIGHTLY",
         Channel::Dev => "CHANNEL_DEV",
     };
-    println!("cargo:rustc-cfg={channel}")
+    println!("cargo:rustc-cfg={channel}");
+
+    // `#[diagnostic::on_unimplemented]` is stable since 1.78
+    if version_meta().unwrap().semver >= Version::new(1, 78, 0) {
+        println!("cargo:rustc-cfg=DIAGNOSTIC_NAMESPACE");
+    }
 }
@@ -1,20 +1,71 @@
-//! Wrapper around string which is decodable as an enum
+//! Wrapper around string which is de- and encodable as an enum
 
-/// Wrapper around string which is decodable as an enum
+use crate::Error;
+
+/// Wrapper around string which is de- and encodable as an enum
+///
+/// On postgres, it is bound as parameter without declaring a type,
+/// so the server infers the enum type from the context it is used in.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Choice(pub String);
 
+/// Rust enum which is stored as [`Choice`]
+///
+/// Implement it for your enums to decode them using [`Row::get_choice`](crate::Row::get_choice):
+///
+/// ```
+/// use rorm_db::choice::{Choice, ChoiceEnum};
+/// use rorm_db::Error;
+///
+/// enum Color {
+///     Red,
+///     Green,
+/// }
+///
+/// impl ChoiceEnum for Color {
+///     fn from_choice(choice: &Choice) -> Result<Self, Error> {
+///         match choice.0.as_str() {
+///             "Red" => Ok(Color::Red),
+///             "Green" => Ok(Color::Green),
+///             _ => Err(Error::DecodeError(format!("unknown color {:?}", choice.0))),
+///         }
+///     }
+///
+///     fn as_choice(&self) -> Choice {
+///         Choice(String::from(match self {
+///             Color::Red => "Red",
+///             Color::Green => "Green",
+///         }))
+///     }
+/// }
+/// ```
+pub trait ChoiceEnum: Sized {
+    /// Convert a decoded [`Choice`] into the enum
+    ///
+    /// Unknown variants should be reported as [`Error::DecodeError`].
+    fn from_choice(choice: &Choice) -> Result<Self, Error>;
+
+    /// Convert the enum into the [`Choice`] storing it
+    fn as_choice(&self) -> Choice;
+}
+
 #[cfg(feature = "sqlx")]
 const _: () = {
-    use sqlx::database::{Database, HasValueRef};
+    use sqlx::database::{Database, HasArguments, HasValueRef};
+    use sqlx::encode::IsNull;
     use sqlx::error::BoxDynError;
-    use sqlx::{Decode, Type};
+    use sqlx::{Decode, Encode, Type};
 
     #[cfg(feature = "postgres")]
     const _: () = {
+        use sqlx::postgres::types::Oid;
+        use sqlx::postgres::PgTypeInfo;
         use sqlx::Postgres;
         impl Type<Postgres> for Choice {
             fn type_info() -> <Postgres as Database>::TypeInfo {
-                <str as Type<Postgres>>::type_info()
+                // The oid 0 leaves the parameter's type unspecified for the server to infer.
+                // Declaring it as text would be rejected when comparing to or storing in an enum.
+                PgTypeInfo::with_oid(Oid(0))
             }
             fn compatible(_ty: &<Postgres as Database>::TypeInfo) -> bool {
                 true // ugly but the only possible solution at the moment
@@ -25,6 +76,14 @@ const _: () = {
                 <String as Decode<'r, Postgres>>::decode(value).map(Self)
             }
         }
+        impl<'q> Encode<'q, Postgres> for Choice {
+            fn encode_by_ref(
+                &self,
+                buf: &mut <Postgres as HasArguments<'q>>::ArgumentBuffer,
+            ) -> IsNull {
+                <String as Encode<'q, Postgres>>::encode_by_ref(&self.0, buf)
+            }
+        }
     };
 
     #[cfg(feature = "mysql")]
@@ -43,6 +102,14 @@ const _: () = {
                 <String as Decode<'r, MySql>>::decode(value).map(Self)
             }
         }
+        impl<'q> Encode<'q, MySql> for Choice {
+            fn encode_by_ref(
+                &self,
+                buf: &mut <MySql as HasArguments<'q>>::ArgumentBuffer,
+            ) -> IsNull {
+                <String as Encode<'q, MySql>>::encode_by_ref(&self.0, buf)
+            }
+        }
     };
 
     #[cfg(feature = "sqlite")]
@@ -61,5 +128,13 @@ const _: () = {
                 <String as Decode<'r, Sqlite>>::decode(value).map(Self)
             }
         }
+        impl<'q> Encode<'q, Sqlite> for Choice {
+            fn encode_by_ref(
+                &self,
+                buf: &mut <Sqlite as HasArguments<'q>>::ArgumentBuffer,
+            ) -> IsNull {
+                <String as Encode<'q, Sqlite>>::encode_by_ref(&self.0, buf)
+            }
+        }
     };
 };
@@ -0,0 +1,24 @@
+//! This module holds the definition of pinned connections
+
+use crate::database::DatabaseSettings;
+use crate::internal;
+
+/**
+A single connection taken from the database's pool
+
+Can be obtained using [`Database::with_connection`](crate::Database::with_connection).
+
+Every statement executed on a `&mut Connection` runs on the same connection,
+which is required for session state like SQLite's pragmas or temporary tables
+outside a transaction.
+
+# Executor
+`&mut Connection` implements [`Executor`](crate::executor::Executor),
+but its [`ensure_transaction`](crate::executor::Executor::ensure_transaction) always fails.
+Functions which require a transaction (e.g. [`insert_bulk`](crate::database::insert_bulk))
+should use a [`Transaction`](crate::transaction::Transaction) instead.
+ */
+pub struct Connection(
+    pub(crate) internal::connection::Impl,
+    pub(crate) DatabaseSettings,
+);
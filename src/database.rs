@@ -1,25 +1,38 @@
 //! [`Database`] struct and several common operations
 
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
+use futures::future::BoxFuture;
+use futures::StreamExt;
 use log::{debug, warn, LevelFilter};
 use rorm_declaration::config::DatabaseDriver;
 use rorm_sql::delete::Delete;
 use rorm_sql::insert::Insert;
 use rorm_sql::join_table::JoinTableData;
+use rorm_sql::limit_clause::LimitClause;
 use rorm_sql::ordering::OrderByEntry;
 use rorm_sql::select::Select;
 use rorm_sql::select_column::SelectColumnData;
 use rorm_sql::update::Update;
 use rorm_sql::value::Value;
-use rorm_sql::{conditional, value};
+use rorm_sql::{conditional, value, DBImpl};
 
+use crate::connection::Connection;
 use crate::error::Error;
-use crate::executor::{AffectedRows, All, Executor, Nothing, One, QueryStrategy};
+use crate::executor::{
+    quote_identifier, AffectedRows, All, Executor, Nothing, One, Optional, QueryStrategy, Stream,
+};
 use crate::internal;
 use crate::query_type::GetLimitClause;
-use crate::row::Row;
-use crate::transaction::Transaction;
+use crate::row::{DecodeOwned, FromRow, Row};
+use crate::transaction::{Nesting, Transaction, TransactionGuard, TransactionState};
 
 /**
 Type alias for [`SelectColumnData`]..
@@ -35,11 +48,28 @@ As all databases use currently the same fields, a type alias is sufficient.
 */
 pub type JoinTable<'until_build, 'post_build> = JoinTableData<'until_build, 'post_build>;
 
+/// Function rewriting a query's sql before it is executed
+///
+/// See [`DatabaseConfiguration::query_rewriter`]
+pub type QueryRewriter = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
 /// Configuration use in [`Database::connect`].
-#[derive(Debug)]
+///
+/// Its `Debug` implementation redacts the driver's password, so the configuration can be logged.
+#[derive(Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DatabaseConfiguration {
     /// The driver and its corresponding settings
+    ///
+    /// Postgres and MySQL connect using a unix domain socket if their `host` starts with a `/`:
+    /// - Postgres expects the directory containing the socket (e.g. `/var/run/postgresql`)
+    ///   and uses `port` to derive the socket's file name.
+    /// - MySQL expects the socket's path (e.g. `/var/run/mysqld/mysqld.sock`) and ignores `port`.
+    ///
+    /// Postgres' `host` may list several comma separated candidates for client-side failover
+    /// (e.g. `primary.example.com,replica.example.com:5433`).
+    /// They are tried in order and the pool stays with the first one accepting a connection.
+    /// Candidates without an explicit port use `port`.
     pub driver: DatabaseDriver,
 
     /// Minimal connections to initialize upfront.
@@ -62,10 +92,343 @@ pub struct DatabaseConfiguration {
     /// In case of None, [`LevelFilter::Debug`] will be used.
     pub statement_log_level: Option<LevelFilter>,
 
-    /// Log level in case of slow statements (>300 ms)
+    /// Log level in case of slow statements
     ///
     /// In case of None, [`LevelFilter::Warn`] will be used.
     pub slow_statement_log_level: Option<LevelFilter>,
+
+    /// Duration after which a statement is considered slow
+    ///
+    /// See [`slow_statement_log_level`](DatabaseConfiguration::slow_statement_log_level).
+    ///
+    /// In case of None, 300 ms will be used.
+    pub slow_statement_threshold: Option<Duration>,
+
+    /// Format of the logged SQL statements
+    ///
+    /// Statements are logged once they have been executed and include the elapsed time.
+    /// Slow statements use the same format.
+    ///
+    /// In case of None, [`StatementLogFormat::Plain`] will be used.
+    pub statement_log_format: Option<StatementLogFormat>,
+
+    /// Log a warning if the last [`Database`] handle is dropped without calling [`Database::close`]
+    #[cfg_attr(feature = "serde", serde(default = "default_true"))]
+    pub warn_on_implicit_drop: bool,
+
+    /// Name to identify the connections with on the database server
+    ///
+    /// Postgres exposes it as `application_name` for example in `pg_stat_activity`.
+    /// MySQL and SQLite ignore it, because their drivers don't support setting it.
+    pub application_name: Option<String>,
+
+    /// Maximum number of savepoints which can be nested inside a transaction
+    ///
+    /// See [`Transaction::start_transaction`].
+    ///
+    /// In case of None, the nesting is not limited.
+    pub max_transaction_depth: Option<u32>,
+
+    /// Label to tell several pools apart in the logs
+    ///
+    /// It prefixes the executed statements' log lines.
+    /// Slow statements are logged by sqlx and therefore not labeled.
+    ///
+    /// In case of None, log lines are not labeled.
+    pub pool_name: Option<String>,
+
+    /// Allow [`Database::execute_batch`] to send several statements at once to MySQL
+    ///
+    /// This is an opt-in check performed by [`Database::execute_batch`] and not a connection setting:
+    /// sqlx always negotiates multiple statements with MySQL,
+    /// so [`Database::execute_unprepared`] runs every statement it is given regardless of this flag.
+    /// Postgres and SQLite always allow multiple statements and ignore the setting.
+    ///
+    /// **Beware** of sql injections:
+    /// a value inserted into a batch without escaping it can append arbitrary statements.
+    /// Prepared statements (i.e. everything using [`Executor`]) never allow multiple statements.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub allow_multiple_statements: bool,
+
+    /// Rewrites every query executed through [`Executor::execute`] before its values are bound
+    ///
+    /// This can be used to add comments to the sql (e.g. `/* request_id=... */`)
+    /// to correlate statements on the database server with the application's traces.
+    /// The rewritten query is what gets logged.
+    /// On MySQL, a transaction's `SELECT` already carries the `MAX_EXECUTION_TIME` hint
+    /// added by [`Transaction::set_statement_timeout`] when it is passed to the rewriter.
+    ///
+    /// The rewriter must not add, remove or reorder placeholders,
+    /// because the values are bound to the placeholders by position.
+    ///
+    /// In case of None, queries are executed unchanged.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub query_rewriter: Option<QueryRewriter>,
+
+    /// Whether and how to use TLS for the connections
+    ///
+    /// Requires one of the `rustls` or `native-tls` features to use TLS.
+    /// SQLite ignores all TLS settings.
+    ///
+    /// In case of None, the driver's default is used, which is [`SslMode::Prefer`].
+    pub ssl_mode: Option<SslMode>,
+
+    /// Path to the PEM file of the certificate authority to verify the server's certificate with
+    ///
+    /// In case of None, the system's root certificates are used.
+    pub ssl_root_cert: Option<PathBuf>,
+
+    /// Path to the PEM file of the client certificate to authenticate with
+    ///
+    /// Requires [`ssl_client_key`](DatabaseConfiguration::ssl_client_key) to be set as well.
+    pub ssl_client_cert: Option<PathBuf>,
+
+    /// Path to the PEM file of the client certificate's private key
+    pub ssl_client_key: Option<PathBuf>,
+
+    /// Isolation level used by [`Database::start_transaction`]
+    ///
+    /// It can be overwritten for a single transaction using
+    /// [`Database::start_transaction_with_isolation_level`].
+    ///
+    /// In case of None, the database server's default is used.
+    pub default_isolation_level: Option<IsolationLevel>,
+
+    /// Maximum time [`Database::start_transaction`] waits for a connection and begins the transaction
+    ///
+    /// Once it passed, [`Error::Timeout`] is returned,
+    /// for example when all connections are held by other transactions.
+    ///
+    /// In case of None, starting a transaction waits indefinitely.
+    pub begin_timeout: Option<Duration>,
+
+    /// Statement executed on a pooled connection before it is handed out
+    ///
+    /// This can be used as health check (e.g. `SELECT 1`).
+    /// If it fails, the connection is closed and another one is acquired.
+    ///
+    /// In case of None, connections are handed out without executing anything.
+    pub before_acquire: Option<String>,
+
+    /// Statement executed on a connection after it has been returned to the pool
+    ///
+    /// This can be used to reset the connection's session state
+    /// (e.g. `DISCARD ALL` when using Postgres behind PgBouncer).
+    /// Afterwards, the connection's cache of prepared statements is cleared,
+    /// because the statement might have dropped them.
+    /// If it fails, the connection is closed instead of being reused.
+    ///
+    /// In case of None, connections are reused without executing anything.
+    pub after_release: Option<String>,
+
+    /// Maximum number of values bound to a single statement by the bulk operations
+    ///
+    /// [`insert_bulk`] and its variants insert fewer rows per statement
+    /// if the rows have too many columns to stay below this limit.
+    ///
+    /// In case of None, the database's default limit is used:
+    /// 65535 for Postgres and MySQL and 999 (`SQLITE_MAX_VARIABLE_NUMBER` of older versions) for SQLite.
+    pub max_bind_parameters: Option<usize>,
+
+    /// Table and version the database's schema is expected to be at
+    ///
+    /// After connecting, the highest value of the table's `version` column is compared to the version.
+    /// If they don't match, the pool is closed again and an [`Error::ConfigurationError`] is returned.
+    /// This guards against using a database whose migrations haven't been applied.
+    ///
+    /// In case of None, the schema is not checked.
+    pub expected_schema_version: Option<(String, i64)>,
+}
+
+/// Format of the logged SQL statements
+///
+/// See [`DatabaseConfiguration::statement_log_format`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum StatementLogFormat {
+    /// The statement as is, prefixed by the [`pool_name`](DatabaseConfiguration::pool_name)
+    /// and followed by the elapsed time
+    ///
+    /// `[main] SELECT 1 (3 ms)`
+    Plain,
+
+    /// A json object containing the statement under `"sql"`, the elapsed milliseconds under `"elapsed_ms"`
+    /// and the [`pool_name`](DatabaseConfiguration::pool_name) under `"pool"` if it is set
+    ///
+    /// `{"sql": "SELECT 1", "elapsed_ms": 3, "pool": "main"}`
+    Json,
+}
+
+/// Whether and how to use TLS to connect to the database
+///
+/// See [`DatabaseConfiguration::ssl_mode`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum SslMode {
+    /// Never use TLS
+    Disable,
+
+    /// Use TLS if the server supports it
+    Prefer,
+
+    /// Always use TLS, but don't verify the server's certificate
+    Require,
+
+    /// Always use TLS and verify the server's certificate against the certificate authority
+    VerifyCa,
+
+    /// Always use TLS and verify the server's certificate and its host name
+    ///
+    /// MySQL calls this `VERIFY_IDENTITY`.
+    VerifyFull,
+}
+
+/// Isolation level of a transaction
+///
+/// See [`DatabaseConfiguration::default_isolation_level`]
+///
+/// SQLite's transactions are always serializable and ignore the isolation level.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum IsolationLevel {
+    /// `READ UNCOMMITTED`
+    ///
+    /// Postgres treats it like [`IsolationLevel::ReadCommitted`].
+    ReadUncommitted,
+
+    /// `READ COMMITTED`
+    ReadCommitted,
+
+    /// `REPEATABLE READ`
+    RepeatableRead,
+
+    /// `SERIALIZABLE`
+    Serializable,
+}
+
+impl IsolationLevel {
+    /// The level's name in sql
+    pub(crate) fn as_sql(self) -> &'static str {
+        match self {
+            IsolationLevel::ReadUncommitted => "READ UNCOMMITTED",
+            IsolationLevel::ReadCommitted => "READ COMMITTED",
+            IsolationLevel::RepeatableRead => "REPEATABLE READ",
+            IsolationLevel::Serializable => "SERIALIZABLE",
+        }
+    }
+}
+
+impl fmt::Debug for DatabaseConfiguration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            driver,
+            min_connections,
+            max_connections,
+            disable_logging,
+            statement_log_level,
+            slow_statement_log_level,
+            slow_statement_threshold,
+            statement_log_format,
+            warn_on_implicit_drop,
+            application_name,
+            max_transaction_depth,
+            pool_name,
+            allow_multiple_statements,
+            query_rewriter,
+            ssl_mode,
+            ssl_root_cert,
+            ssl_client_cert,
+            ssl_client_key,
+            default_isolation_level,
+            begin_timeout,
+            before_acquire,
+            after_release,
+            max_bind_parameters,
+            expected_schema_version,
+        } = self;
+        f.debug_struct("DatabaseConfiguration")
+            .field("driver", &RedactedDriver(driver))
+            .field("min_connections", min_connections)
+            .field("max_connections", max_connections)
+            .field("disable_logging", disable_logging)
+            .field("statement_log_level", statement_log_level)
+            .field("slow_statement_log_level", slow_statement_log_level)
+            .field("slow_statement_threshold", slow_statement_threshold)
+            .field("statement_log_format", statement_log_format)
+            .field("warn_on_implicit_drop", warn_on_implicit_drop)
+            .field("application_name", application_name)
+            .field("max_transaction_depth", max_transaction_depth)
+            .field("pool_name", pool_name)
+            .field("allow_multiple_statements", allow_multiple_statements)
+            .field(
+                "query_rewriter",
+                &query_rewriter.as_ref().map(|_| "<function>"),
+            )
+            .field("ssl_mode", ssl_mode)
+            .field("ssl_root_cert", ssl_root_cert)
+            .field("ssl_client_cert", ssl_client_cert)
+            .field("ssl_client_key", ssl_client_key)
+            .field("default_isolation_level", default_isolation_level)
+            .field("begin_timeout", begin_timeout)
+            .field("before_acquire", before_acquire)
+            .field("after_release", after_release)
+            .field("max_bind_parameters", max_bind_parameters)
+            .field("expected_schema_version", expected_schema_version)
+            .finish()
+    }
+}
+
+/// `Debug` implementation of a [`DatabaseDriver`] which doesn't print its password
+struct RedactedDriver<'a>(&'a DatabaseDriver);
+
+impl fmt::Debug for RedactedDriver<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            #[cfg(feature = "sqlite")]
+            DatabaseDriver::SQLite { filename } => f
+                .debug_struct("SQLite")
+                .field("filename", filename)
+                .finish(),
+            #[cfg(feature = "postgres")]
+            DatabaseDriver::Postgres {
+                name,
+                host,
+                port,
+                user,
+                password: _,
+            } => f
+                .debug_struct("Postgres")
+                .field("name", name)
+                .field("host", host)
+                .field("port", port)
+                .field("user", user)
+                .field("password", &format_args!("<redacted>"))
+                .finish(),
+            #[cfg(feature = "mysql")]
+            DatabaseDriver::MySQL {
+                name,
+                host,
+                port,
+                user,
+                password: _,
+            } => f
+                .debug_struct("MySQL")
+                .field("name", name)
+                .field("host", host)
+                .field("port", port)
+                .field("user", user)
+                .field("password", &format_args!("<redacted>"))
+                .finish(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+fn default_true() -> bool {
+    true
 }
 
 impl DatabaseConfiguration {
@@ -78,6 +441,24 @@ impl DatabaseConfiguration {
     - `disable_logging`: None
     - `statement_log_level`: [`Some`] of [`LevelFilter::Debug`]
     - `slow_statement_log_level`: [`Some`] of [`LevelFilter::Warn`]
+    - `slow_statement_threshold`: None
+    - `statement_log_format`: None
+    - `warn_on_implicit_drop`: true
+    - `application_name`: None
+    - `max_transaction_depth`: None
+    - `pool_name`: None
+    - `allow_multiple_statements`: false
+    - `query_rewriter`: None
+    - `ssl_mode`: None
+    - `ssl_root_cert`: None
+    - `ssl_client_cert`: None
+    - `ssl_client_key`: None
+    - `default_isolation_level`: None
+    - `begin_timeout`: None
+    - `before_acquire`: None
+    - `after_release`: None
+    - `max_bind_parameters`: None
+    - `expected_schema_version`: None
 
     **Parameter**:
     - `driver`: [`DatabaseDriver`]: Configuration of the database driver.
@@ -90,6 +471,47 @@ impl DatabaseConfiguration {
             disable_logging: None,
             statement_log_level: Some(LevelFilter::Debug),
             slow_statement_log_level: Some(LevelFilter::Warn),
+            slow_statement_threshold: None,
+            statement_log_format: None,
+            warn_on_implicit_drop: true,
+            application_name: None,
+            max_transaction_depth: None,
+            pool_name: None,
+            allow_multiple_statements: false,
+            query_rewriter: None,
+            ssl_mode: None,
+            ssl_root_cert: None,
+            ssl_client_cert: None,
+            ssl_client_key: None,
+            default_isolation_level: None,
+            begin_timeout: None,
+            before_acquire: None,
+            after_release: None,
+            max_bind_parameters: None,
+            expected_schema_version: None,
+        }
+    }
+
+    /**
+    Create a new database configuration whose `max_connections` is derived from the cpu count.
+
+    `max_connections` is set to twice [`std::thread::available_parallelism`]
+    clamped to the range `4..=64`.
+    If the parallelism can't be determined, it is assumed to be `1`.
+
+    All other fields use the same defaults as [`DatabaseConfiguration::new`].
+
+    **Parameter**:
+    - `driver`: [`DatabaseDriver`]: Configuration of the database driver.
+    */
+    pub fn new_auto_sized(driver: DatabaseDriver) -> Self {
+        let parallelism = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let max_connections = u32::try_from(parallelism.saturating_mul(2))
+            .unwrap_or(u32::MAX)
+            .clamp(4, 64);
+        DatabaseConfiguration {
+            max_connections,
+            ..Self::new(driver)
         }
     }
 }
@@ -103,15 +525,218 @@ impl DatabaseConfiguration {
 ///
 /// Cloning is cheap i.e. two `Arc`s.
 #[derive(Clone)]
-pub struct Database(pub(crate) internal::database::Impl, Arc<()>);
+pub struct Database(
+    pub(crate) internal::database::Impl,
+    /// Whether the pool is draining, shared by all handles
+    Arc<AtomicBool>,
+    pub(crate) DatabaseSettings,
+);
+
+/// Settings taken from the [`DatabaseConfiguration`] which are carried by every [`Database`] handle
+#[derive(Clone)]
+pub(crate) struct DatabaseSettings {
+    /// See [`DatabaseConfiguration::warn_on_implicit_drop`]
+    pub(crate) warn_on_implicit_drop: bool,
+
+    /// See [`DatabaseConfiguration::max_transaction_depth`]
+    pub(crate) max_transaction_depth: Option<u32>,
+
+    /// See [`DatabaseConfiguration::statement_log_level`] and [`Database::with_statement_log_level`]
+    pub(crate) statement_log_level: LevelFilter,
+
+    /// See [`DatabaseConfiguration::slow_statement_log_level`]
+    pub(crate) slow_statement_log_level: LevelFilter,
+
+    /// See [`DatabaseConfiguration::slow_statement_threshold`]
+    pub(crate) slow_statement_threshold: Duration,
+
+    /// See [`DatabaseConfiguration::statement_log_format`]
+    pub(crate) statement_log_format: StatementLogFormat,
+
+    /// See [`DatabaseConfiguration::pool_name`]
+    pub(crate) pool_name: Option<Arc<str>>,
+
+    /// See [`DatabaseConfiguration::allow_multiple_statements`]
+    pub(crate) allow_multiple_statements: bool,
+
+    /// See [`DatabaseConfiguration::query_rewriter`]
+    pub(crate) query_rewriter: Option<QueryRewriter>,
+
+    /// See [`DatabaseConfiguration::default_isolation_level`]
+    pub(crate) default_isolation_level: Option<IsolationLevel>,
+
+    /// See [`DatabaseConfiguration::begin_timeout`]
+    pub(crate) begin_timeout: Option<Duration>,
+
+    /// See [`DatabaseConfiguration::max_bind_parameters`]
+    pub(crate) max_bind_parameters: Option<usize>,
+
+    /// Runtime the pool has been created in, used by [`Database::execute_blocking`]
+    #[cfg(feature = "blocking")]
+    pub(crate) runtime: Option<tokio::runtime::Handle>,
+}
 
 impl Database {
     /// Connects to the database using `configuration`
     pub async fn connect(configuration: DatabaseConfiguration) -> Result<Self, Error> {
-        Ok(Self(
+        Self::connect_ref(&configuration).await
+    }
+
+    /// Connects to the database using `configuration`, retrying if the database isn't reachable yet
+    ///
+    /// This is useful when the application might start before the database is ready to accept connections.
+    ///
+    /// Transient errors like a refused connection or a database which is still starting up
+    /// are retried up to `retries` times.
+    /// The first retry waits for `backoff` and each further one waits twice as long as the one before.
+    /// Other errors like invalid credentials are returned immediately.
+    pub async fn connect_with_retry(
+        configuration: DatabaseConfiguration,
+        retries: u32,
+        backoff: Duration,
+    ) -> Result<Self, Error> {
+        let mut backoff = backoff;
+        let mut retries_left = retries;
+        loop {
+            match Self::connect_ref(&configuration).await {
+                Err(error) if retries_left > 0 && internal::database::is_transient(&error) => {
+                    warn!("Failed to connect to the database, retrying in {backoff:?}: {error}");
+                    internal::database::sleep(backoff).await;
+                    backoff = backoff.saturating_mul(2);
+                    retries_left -= 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Implementation of [`Database::connect`] and [`Database::connect_with_retry`]
+    async fn connect_ref(configuration: &DatabaseConfiguration) -> Result<Self, Error> {
+        let disabled_logging = configuration.disable_logging.unwrap_or(false);
+        let settings = DatabaseSettings {
+            warn_on_implicit_drop: configuration.warn_on_implicit_drop,
+            max_transaction_depth: configuration.max_transaction_depth,
+            statement_log_level: if disabled_logging {
+                LevelFilter::Off
+            } else {
+                configuration
+                    .statement_log_level
+                    .unwrap_or(LevelFilter::Debug)
+            },
+            slow_statement_log_level: if disabled_logging {
+                LevelFilter::Off
+            } else {
+                configuration
+                    .slow_statement_log_level
+                    .unwrap_or(LevelFilter::Warn)
+            },
+            slow_statement_threshold: configuration
+                .slow_statement_threshold
+                .unwrap_or(SLOW_STATEMENTS),
+            statement_log_format: configuration
+                .statement_log_format
+                .unwrap_or(StatementLogFormat::Plain),
+            pool_name: configuration.pool_name.as_deref().map(Arc::from),
+            allow_multiple_statements: configuration.allow_multiple_statements,
+            query_rewriter: configuration.query_rewriter.clone(),
+            default_isolation_level: configuration.default_isolation_level,
+            begin_timeout: configuration.begin_timeout,
+            max_bind_parameters: configuration.max_bind_parameters,
+            #[cfg(feature = "blocking")]
+            runtime: tokio::runtime::Handle::try_current().ok(),
+        };
+        let database = Self(
             internal::database::connect(configuration).await?,
-            Arc::new(()),
-        ))
+            Arc::new(AtomicBool::new(false)),
+            settings,
+        );
+        if let Some((table, expected)) = &configuration.expected_schema_version {
+            if let Err(error) = database.check_schema_version(table, *expected).await {
+                database.close().await;
+                return Err(error);
+            }
+        }
+        Ok(database)
+    }
+
+    /// Checks the [`DatabaseConfiguration::expected_schema_version`]
+    async fn check_schema_version(&self, table: &str, expected: i64) -> Result<(), Error> {
+        let dialect = self.dialect();
+        let query = format!(
+            "SELECT MAX({}) FROM {};",
+            quote_identifier(dialect, "version"),
+            quote_identifier(dialect, table)
+        );
+        let row = self.execute::<One>(query, Vec::new()).await?;
+        // Postgres doesn't widen an `INTEGER` column's maximum to `BIGINT`
+        let version = row.get::<Option<i64>, _>(0).or_else(|_| {
+            row.get::<Option<i32>, _>(0)
+                .map(|version| version.map(i64::from))
+        })?;
+        match version {
+            Some(version) if version == expected => Ok(()),
+            Some(version) => Err(Error::ConfigurationError(format!(
+                "expected schema version {expected} in {table}, but the database is at version {version}"
+            ))),
+            None => Err(Error::ConfigurationError(format!(
+                "expected schema version {expected} in {table}, but the table is empty"
+            ))),
+        }
+    }
+
+    /// The maximum number of values the bulk operations bind to a single statement
+    ///
+    /// See [`DatabaseConfiguration::max_bind_parameters`]
+    pub fn max_bind_parameters(&self) -> usize {
+        self.2.max_bind_parameters(self.dialect())
+    }
+
+    /// Creates another handle to the same connection pool which logs its statements at `level`
+    ///
+    /// This allows a noisy part of an application to use a quieter (or louder) log level
+    /// without opening a second pool.
+    /// Transactions started from the returned handle inherit its log level.
+    ///
+    /// Slow statements are still logged as configured in
+    /// [`DatabaseConfiguration::slow_statement_log_level`].
+    pub fn with_statement_log_level(&self, level: LevelFilter) -> Database {
+        let mut database = self.clone();
+        database.2.statement_log_level = level;
+        database
+    }
+
+    /// Executes a query from a synchronous context by blocking the current thread
+    ///
+    /// The query is driven by the tokio runtime [`Database::connect`] has been called in.
+    ///
+    /// Only strategies resolving to a future are supported
+    /// i.e. every strategy except [`Stream`](crate::executor::Stream).
+    ///
+    /// # Errors
+    /// Blocking inside the runtime could deadlock,
+    /// so calling this method from an async context returns an [`Error::ConfigurationError`].
+    /// The same error is returned if the database wasn't connected inside a tokio runtime.
+    #[cfg(feature = "blocking")]
+    pub fn execute_blocking<'db, Q, T>(
+        &'db self,
+        query: String,
+        values: Vec<Value<'db>>,
+    ) -> Result<T, Error>
+    where
+        Q: QueryStrategy,
+        Q::Result<'db>: Future<Output = Result<T, Error>>,
+    {
+        if tokio::runtime::Handle::try_current().is_ok() {
+            return Err(Error::ConfigurationError(String::from(
+                "execute_blocking must not be called from within an async runtime",
+            )));
+        }
+        let Some(runtime) = self.2.runtime.as_ref() else {
+            return Err(Error::ConfigurationError(String::from(
+                "execute_blocking requires the database to be connected inside a tokio runtime",
+            )));
+        };
+        runtime.block_on(self.execute::<Q>(query, values))
     }
 
     /**
@@ -140,13 +765,270 @@ impl Database {
         internal::database::raw_sql(self, query_string, bind_params, transaction).await
     }
 
+    /// Executes a raw SQL query and returns all rows
+    ///
+    /// Shorthand for [`Executor::execute`] using the [`All`] strategy.
+    pub async fn fetch_all(
+        &self,
+        query: String,
+        values: Vec<Value<'_>>,
+    ) -> Result<Vec<Row>, Error> {
+        self.execute::<All>(query, values).await
+    }
+
+    /// Executes a raw SQL query and returns its first row
+    ///
+    /// Shorthand for [`Executor::execute`] using the [`One`] strategy.
+    pub async fn fetch_one(&self, query: String, values: Vec<Value<'_>>) -> Result<Row, Error> {
+        self.execute::<One>(query, values).await
+    }
+
+    /// Executes a raw SQL query and returns its first row if there is one
+    ///
+    /// Shorthand for [`Executor::execute`] using the [`Optional`] strategy.
+    pub async fn fetch_optional(
+        &self,
+        query: String,
+        values: Vec<Value<'_>>,
+    ) -> Result<Option<Row>, Error> {
+        self.execute::<Optional>(query, values).await
+    }
+
+    /// Executes a raw SQL query and returns the number of affected rows
+    ///
+    /// Shorthand for [`Executor::execute`] using the [`AffectedRows`] strategy.
+    pub async fn execute_affected(
+        &self,
+        query: String,
+        values: Vec<Value<'_>>,
+    ) -> Result<u64, Error> {
+        self.execute::<AffectedRows>(query, values).await
+    }
+
+    /// Executes a statement outside any transaction without preparing it
+    ///
+    /// This is required for maintenance statements like `VACUUM`
+    /// which can't run inside a transaction or as prepared statement.
+    ///
+    /// If `sql` contains several statements separated by `;`, every driver runs all of them,
+    /// on MySQL even without [`DatabaseConfiguration::allow_multiple_statements`].
+    /// Use [`Database::execute_batch`] for scripts.
+    ///
+    /// **Beware**: the statement can't take any parameters,
+    /// so never use it with untrusted input.
+    ///
+    /// **Returns** the number of rows affected by all statements together.
+    pub async fn execute_unprepared(&self, sql: &str) -> Result<u64, Error> {
+        self.check_draining()?;
+        internal::database::execute_unprepared(self, sql).await
+    }
+
+    /// Executes a sql script which may consist of several statements separated by `;`
+    ///
+    /// The script is sent as is without preparing it, so it can't take any parameters.
+    /// It runs outside a transaction unless it contains its own `BEGIN` and `COMMIT`.
+    ///
+    /// MySQL requires [`DatabaseConfiguration::allow_multiple_statements`] to be enabled,
+    /// otherwise an [`Error::ConfigurationError`] is returned.
+    pub async fn execute_batch(&self, sql: &str) -> Result<(), Error> {
+        self.check_draining()?;
+        internal::database::execute_batch(self, sql).await
+    }
+
+    /// Reads a sql script from a file and executes it using [`Database::execute_batch`]
+    ///
+    /// The file is read completely into memory before it is executed
+    /// using the runtime's non-blocking file access.
+    /// If it can't be read, an [`Error::IoError`] is returned.
+    pub async fn execute_file(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let sql = internal::database::read_to_string(path.as_ref()).await?;
+        self.execute_batch(&sql).await
+    }
+
+    /// Prepares a statement to be executed repeatedly with different values
+    ///
+    /// The statement is prepared once to report errors in `sql` early.
+    /// Afterwards, sqlx caches the prepared statement per connection,
+    /// so each pooled connection prepares it again when it executes the statement for the first time.
+    ///
+    /// To execute the statement inside a transaction,
+    /// pass [`PreparedStatement::sql`] to the transaction's [`Executor::execute`].
+    pub async fn prepare(&self, sql: &str) -> Result<PreparedStatement, Error> {
+        self.check_draining()?;
+        internal::database::prepare(self, sql).await?;
+        Ok(PreparedStatement {
+            database: self.clone(),
+            sql: Arc::from(sql),
+        })
+    }
+
+    /// Lists the names of all tables in the current schema (postgres) or database (mysql and sqlite)
+    ///
+    /// The names are sorted and exclude views and sqlite's internal tables.
+    pub async fn list_tables(&self) -> Result<Vec<String>, Error> {
+        let query = format!("{} ORDER BY name;", tables_sql(self.dialect()));
+        self.execute::<All>(query, Vec::new())
+            .await?
+            .iter()
+            .map(|row| row.get(0))
+            .collect()
+    }
+
+    /// Checks whether a table exists in the current schema (postgres) or database (mysql and sqlite)
+    ///
+    /// See [`Database::list_tables`]
+    pub async fn table_exists(&self, name: &str) -> Result<bool, Error> {
+        let dialect = self.dialect();
+        let query = format!(
+            "SELECT COUNT(*) FROM ({}) AS tables WHERE name = {};",
+            tables_sql(dialect),
+            placeholder(dialect, 1)
+        );
+        let row = self
+            .execute::<One>(query, vec![Value::String(name)])
+            .await?;
+        Ok(row.get::<i64, _>(0)? > 0)
+    }
+
+    /// Tries to acquire the advisory lock identified by `key` without waiting for it
+    ///
+    /// Advisory locks are held by the database server and can therefore coordinate several processes,
+    /// e.g. to let only one of several replicas run the migrations.
+    /// The lock belongs to the session of a connection which is taken from the pool
+    /// and kept by the returned [`AdvisoryLock`] until it is [released](AdvisoryLock::release).
+    ///
+    /// - Postgres uses a session level lock (`pg_try_advisory_lock`).
+    /// - MySQL uses a named lock (`GET_LOCK`) called `rorm_db_<key>`.
+    /// - SQLite only allows a single writer anyway, so the lock is always acquired
+    ///   without occupying a connection.
+    ///
+    /// **Returns** `None` if the lock is currently held by someone else.
+    pub async fn try_advisory_lock(&self, key: i64) -> Result<Option<AdvisoryLock>, Error> {
+        let dialect = self.dialect();
+        let query = match dialect {
+            #[cfg(feature = "postgres")]
+            DBImpl::Postgres => {
+                Some("SELECT CAST(CASE WHEN pg_try_advisory_lock($1) THEN 1 ELSE 0 END AS BIGINT);")
+            }
+            #[cfg(feature = "mysql")]
+            DBImpl::MySQL => Some("SELECT GET_LOCK(CONCAT('rorm_db_', ?), 0);"),
+            #[cfg(feature = "sqlite")]
+            DBImpl::SQLite => None,
+        };
+        let Some(query) = query else {
+            return Ok(Some(AdvisoryLock {
+                connection: None,
+                dialect,
+                key,
+            }));
+        };
+        self.check_draining()?;
+        let mut connection = Connection(internal::database::acquire(self).await?, self.2.clone());
+        let row = connection
+            .execute::<One>(query.to_string(), vec![Value::I64(key)])
+            .await?;
+        // MySQL returns NULL on errors
+        if row.get::<Option<i64>, _>(0)? != Some(1) {
+            return Ok(None);
+        }
+        Ok(Some(AdvisoryLock {
+            connection: Some(connection),
+            dialect,
+            key,
+        }))
+    }
+
     /// Starts a new transaction
     ///
     /// `&mut Transaction` implements [`Executor`] like `&Database` does
     /// but its database operations can be reverted using [`Transaction::rollback`]
     /// or simply dropping the transaction without calling [`Transaction::commit`].
+    ///
+    /// The transaction uses the [`DatabaseConfiguration::default_isolation_level`].
     pub async fn start_transaction(&self) -> Result<Transaction, Error> {
-        internal::database::start_transaction(self).await
+        self.begin(self.2.default_isolation_level).await
+    }
+
+    /// Starts a new transaction using a specific isolation level
+    ///
+    /// This overwrites the [`DatabaseConfiguration::default_isolation_level`] for this transaction.
+    /// See [`Database::start_transaction`] for details.
+    pub async fn start_transaction_with_isolation_level(
+        &self,
+        isolation_level: IsolationLevel,
+    ) -> Result<Transaction, Error> {
+        self.begin(Some(isolation_level)).await
+    }
+
+    /// Implementation of [`Database::start_transaction`]
+    /// and [`Database::start_transaction_with_isolation_level`]
+    async fn begin(&self, isolation_level: Option<IsolationLevel>) -> Result<Transaction, Error> {
+        self.check_draining()?;
+        Ok(Transaction(
+            internal::database::start_transaction(self, isolation_level).await?,
+            Nesting::new(self.2.max_transaction_depth),
+            self.2.clone(),
+            TransactionState::default(),
+        ))
+    }
+
+    /// Runs `f` on a single connection taken from the pool
+    ///
+    /// The connection is pinned for `f`'s duration and returned to the pool afterwards.
+    /// Use this for session state which has to be set up outside a transaction,
+    /// e.g. SQLite's `PRAGMA foreign_keys` which is a no-op inside one.
+    ///
+    /// A [`Connection`] can't start a transaction, so functions which require one
+    /// (e.g. [`insert_bulk`]) fail when passed the connection.
+    /// Call them with a [`Transaction`] from [`Database::start_transaction`] instead,
+    /// which doesn't share the connection's session state.
+    ///
+    /// ```skipped
+    /// db.with_connection(|conn| Box::pin(async move {
+    ///     conn.execute::<Nothing>("PRAGMA foreign_keys = OFF".to_string(), vec![]).await?;
+    ///     conn.execute::<Nothing>(query, values).await
+    /// })).await?;
+    /// ```
+    pub async fn with_connection<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        F: for<'conn> FnOnce(&'conn mut Connection) -> BoxFuture<'conn, Result<T, Error>>,
+    {
+        self.check_draining()?;
+        let mut connection = Connection(internal::database::acquire(self).await?, self.2.clone());
+        f(&mut connection).await
+    }
+
+    /// Starts a graceful shutdown by rejecting new work
+    ///
+    /// Afterwards, new queries and transactions on any handle to this pool
+    /// fail fast with an [`Error::ConfigurationError`].
+    /// Queries and transactions which are already running complete normally.
+    ///
+    /// Use [`Database::drain_complete`] to wait for them.
+    pub fn begin_drain(&self) {
+        self.1.store(true, Ordering::Release);
+    }
+
+    /// Waits for all running queries and transactions to finish and closes the pool
+    ///
+    /// Calls [`Database::begin_drain`] if it hasn't been called yet.
+    ///
+    /// Queries which have been created but not polled before this call will fail,
+    /// because they haven't acquired a connection yet.
+    pub async fn drain_complete(&self) {
+        self.begin_drain();
+        internal::database::drain(self).await
+    }
+
+    /// Returns an error if [`Database::begin_drain`] has been called
+    pub(crate) fn check_draining(&self) -> Result<(), Error> {
+        if self.1.load(Ordering::Acquire) {
+            Err(Error::ConfigurationError(String::from(
+                "the database is draining and doesn't accept new queries",
+            )))
+        } else {
+            Ok(())
+        }
     }
 
     /// Closes the database connection
@@ -163,18 +1045,183 @@ impl Database {
     }
 }
 
+/// Query selecting the table names as `name` used by [`Database::list_tables`]
+fn tables_sql(dialect: DBImpl) -> &'static str {
+    match dialect {
+        #[cfg(feature = "postgres")]
+        DBImpl::Postgres => {
+            "SELECT CAST(table_name AS TEXT) AS name FROM information_schema.tables \
+            WHERE table_schema = current_schema() AND table_type = 'BASE TABLE'"
+        }
+        #[cfg(feature = "mysql")]
+        DBImpl::MySQL => {
+            "SELECT CAST(table_name AS CHAR) AS name FROM information_schema.tables \
+            WHERE table_schema = DATABASE() AND table_type = 'BASE TABLE'"
+        }
+        #[cfg(feature = "sqlite")]
+        DBImpl::SQLite => {
+            "SELECT name FROM sqlite_master \
+            WHERE type = 'table' AND name NOT LIKE 'sqlite\\_%' ESCAPE '\\'"
+        }
+    }
+}
+
+/// Statement created by [`Database::prepare`]
+///
+/// Cloning is cheap i.e. a [`Database`] handle and an `Arc`.
+#[derive(Clone)]
+pub struct PreparedStatement {
+    database: Database,
+    sql: Arc<str>,
+}
+
+impl PreparedStatement {
+    /// Executes the statement with `values` bound to its placeholders
+    ///
+    /// See [`Executor::execute`]
+    pub fn execute<'result, 'data: 'result, Q>(
+        &'result self,
+        values: Vec<Value<'data>>,
+    ) -> Q::Result<'result>
+    where
+        Q: QueryStrategy,
+    {
+        self.database.execute::<Q>(self.sql.to_string(), values)
+    }
+
+    /// The statement's sql
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+}
+
+/// Guard of an advisory lock acquired by [`Database::try_advisory_lock`]
+///
+/// The lock should be released explicitly using [`AdvisoryLock::release`].
+/// Dropping it instead spawns the release on the current tokio runtime.
+/// Without one, the connection holding the lock is closed,
+/// which ends its session and thereby releases the lock as well.
+#[must_use = "The lock should be released using AdvisoryLock::release."]
+pub struct AdvisoryLock {
+    /// Connection holding the lock, taken when releasing it
+    ///
+    /// `None` on SQLite which doesn't need one.
+    connection: Option<Connection>,
+    dialect: DBImpl,
+    key: i64,
+}
+
+impl AdvisoryLock {
+    /// Releases the lock and waits for the database to confirm it
+    pub async fn release(mut self) -> Result<(), Error> {
+        match self.connection.take() {
+            Some(connection) => release_advisory_lock(connection, self.dialect, self.key).await,
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for AdvisoryLock {
+    fn drop(&mut self) {
+        if let Some(connection) = self.connection.take() {
+            let (dialect, key) = (self.dialect, self.key);
+            let spawned = internal::connection::spawn(connection, move |connection| async move {
+                if let Err(error) = release_advisory_lock(connection, dialect, key).await {
+                    warn!("Failed to release the advisory lock {key}: {error}");
+                }
+            });
+            if let Err(connection) = spawned {
+                warn!(
+                    "The advisory lock {key} was dropped outside a tokio runtime, \
+                    closing its connection to release it"
+                );
+                internal::connection::close(connection.0);
+            }
+        }
+    }
+}
+
+/// Release an advisory lock held by `connection`'s session
+async fn release_advisory_lock(
+    mut connection: Connection,
+    dialect: DBImpl,
+    key: i64,
+) -> Result<(), Error> {
+    let query = match dialect {
+        #[cfg(feature = "postgres")]
+        DBImpl::Postgres => Some("SELECT pg_advisory_unlock($1);"),
+        #[cfg(feature = "mysql")]
+        DBImpl::MySQL => Some("SELECT RELEASE_LOCK(CONCAT('rorm_db_', ?));"),
+        #[cfg(feature = "sqlite")]
+        DBImpl::SQLite => None,
+    };
+    let Some(query) = query else {
+        return Ok(());
+    };
+    let result = connection
+        .execute::<Nothing>(query.to_string(), vec![Value::I64(key)])
+        .await;
+    if result.is_err() {
+        // Don't return a connection which might still hold the lock to the pool
+        internal::connection::close(connection.0);
+    }
+    result
+}
+
 impl Drop for Database {
     /// Checks whether [`Database::close`] has been called before the last instance is dropped
     fn drop(&mut self) {
         // The use of strong_count should be correct:
         // - the arc is private and we don't create WeakRefs
         // => when observing a strong_count of 1, there can't be any remaining refs
-        if Arc::strong_count(&self.1) == 1 && !internal::database::is_closed(self) {
-            warn!("Database has been dropped without calling close. This might case the last queries to not being flushed properly");
+        if self.2.warn_on_implicit_drop
+            && Arc::strong_count(&self.1) == 1
+            && !internal::database::is_closed(self)
+        {
+            warn!(
+                "{}Database has been dropped without calling close. This might case the last queries to not being flushed properly",
+                self.2.log_prefix()
+            );
+        }
+    }
+}
+
+impl DatabaseSettings {
+    /// Prefix for log lines to identify the pool they originate from
+    pub(crate) fn log_prefix(&self) -> String {
+        match &self.pool_name {
+            Some(pool_name) => format!("[{pool_name}] "),
+            None => String::new(),
+        }
+    }
+
+    /// The [`DatabaseConfiguration::max_bind_parameters`] falling back to the dialect's default
+    pub(crate) fn max_bind_parameters(&self, dialect: DBImpl) -> usize {
+        self.max_bind_parameters
+            .unwrap_or_else(|| default_max_bind_parameters(dialect))
+    }
+
+    /// Applies the [`DatabaseConfiguration::query_rewriter`] if there is one
+    pub(crate) fn rewrite_query(&self, query: String) -> String {
+        match &self.query_rewriter {
+            Some(rewriter) => rewriter(&query),
+            None => query,
         }
     }
 }
 
+/// The default of [`DatabaseConfiguration::max_bind_parameters`] for `dialect`
+fn default_max_bind_parameters(dialect: DBImpl) -> usize {
+    match dialect {
+        #[cfg(feature = "mysql")]
+        DBImpl::MySQL => 65535,
+        #[cfg(feature = "postgres")]
+        DBImpl::Postgres => 65535,
+        #[cfg(feature = "sqlite")]
+        DBImpl::SQLite => 999,
+    }
+}
+
 /// Executes a simple `SELECT` query.
 ///
 /// It is generic over a [`QueryStrategy`] which specifies how and how many rows to query.
@@ -188,7 +1235,8 @@ impl Drop for Database {
 /// - `limit`: Optional limit / offset to apply to the query.
 ///     Depending on the query strategy, this is either [`LimitClause`](rorm_sql::limit_clause::LimitClause)
 ///     (for [`All`] and [`Stream`](crate::executor::Stream))
-///     or a simple [`u64`] (for [`One`] and [`Optional`](crate::executor::Optional)).
+///     or a simple [`u64`] (for [`One`], [`Optional`](crate::executor::Optional),
+///     [`ExactlyOne`](crate::executor::ExactlyOne) and [`AtMostOne`](crate::executor::AtMostOne)).
 #[allow(clippy::too_many_arguments)]
 pub fn query<'result, 'db: 'result, 'post_query: 'result, Q: QueryStrategy + GetLimitClause>(
     executor: impl Executor<'db>,
@@ -197,44 +1245,301 @@ pub fn query<'result, 'db: 'result, 'post_query: 'result, Q: QueryStrategy + Get
     joins: &[JoinTable<'_, 'post_query>],
     conditions: Option<&conditional::Condition<'post_query>>,
     order_by_clause: &[OrderByEntry<'_>],
-    limit: Option<Q::LimitOrOffset>,
-) -> Q::Result<'result> {
+    limit: Option<Q::LimitOrOffset>,
+) -> Q::Result<'result> {
+    let (query_string, bind_params) = build_select(
+        executor.dialect(),
+        model,
+        columns,
+        joins,
+        conditions,
+        order_by_clause,
+        Q::get_limit_clause(limit),
+    );
+
+    debug!("SQL: {}", query_string);
+
+    executor.execute::<Q>(query_string, bind_params)
+}
+
+/// Executes a simple `SELECT` query as [`Stream`] which ends early once `cancelled` resolves.
+///
+/// This integrates streams with cancellation mechanisms like tokio-util's `CancellationToken`
+/// by passing `token.cancelled_owned()`.
+/// The token is checked whenever the stream is polled.
+///
+/// Cancelling ends the stream without an error and drops the underlying query.
+/// The database isn't asked to cancel the query,
+/// instead the remaining rows are discarded before the connection is reused.
+///
+/// The remaining parameters are the same as [`query`]'s for the [`Stream`] strategy.
+#[allow(clippy::too_many_arguments)]
+pub fn query_stream_cancellable<'result, 'db: 'result, 'post_query: 'result>(
+    executor: impl Executor<'db>,
+    model: &str,
+    columns: &[ColumnSelector<'_>],
+    joins: &[JoinTable<'_, 'post_query>],
+    conditions: Option<&conditional::Condition<'post_query>>,
+    order_by_clause: &[OrderByEntry<'_>],
+    limit: Option<LimitClause>,
+    cancelled: impl Future<Output = ()> + 'result,
+) -> impl futures::Stream<Item = Result<Row, Error>> + 'result {
+    query::<Stream>(
+        executor,
+        model,
+        columns,
+        joins,
+        conditions,
+        order_by_clause,
+        limit,
+    )
+    .take_until(cancelled)
+}
+
+/// Executes a simple `SELECT` query and folds its rows into an accumulator.
+///
+/// The rows are streamed and passed to `f` one at a time,
+/// so only the accumulator is kept in memory instead of all rows like [`All`] would.
+/// The first error returned by the query or `f` stops the query and is returned.
+///
+/// **Parameter**:
+/// - `init`: Initial value of the accumulator.
+/// - `f`: Function combining the accumulator with the next row.
+/// - The remaining parameters are the same as [`query`]'s for the [`Stream`] strategy.
+#[allow(clippy::too_many_arguments)]
+pub async fn query_fold<'post_query, S>(
+    executor: impl Executor<'_>,
+    model: &str,
+    columns: &[ColumnSelector<'_>],
+    joins: &[JoinTable<'_, 'post_query>],
+    conditions: Option<&conditional::Condition<'post_query>>,
+    order_by_clause: &[OrderByEntry<'_>],
+    limit: Option<LimitClause>,
+    init: S,
+    mut f: impl FnMut(S, Row) -> Result<S, Error>,
+) -> Result<S, Error> {
+    let stream = query::<Stream>(
+        executor,
+        model,
+        columns,
+        joins,
+        conditions,
+        order_by_clause,
+        limit,
+    );
+    futures::pin_mut!(stream);
+
+    let mut accumulator = init;
+    while let Some(row) = stream.next().await {
+        accumulator = f(accumulator, row?)?;
+    }
+    Ok(accumulator)
+}
+
+/// Executes a simple `SELECT` query and collects its rows into a map.
+///
+/// Every row's key is decoded from its column `key_column`
+/// and its value is decoded from the whole row using [`FromRow`], i.e. including the key's column.
+/// If several rows share a key, the last one wins.
+///
+/// The rows are streamed like in [`query_fold`],
+/// so they don't have to be kept in memory besides the resulting map.
+///
+/// The remaining parameters are the same as [`query`]'s for the [`Stream`] strategy.
+#[allow(clippy::too_many_arguments)]
+pub async fn query_map<'post_query, K, V>(
+    executor: impl Executor<'_>,
+    model: &str,
+    columns: &[ColumnSelector<'_>],
+    joins: &[JoinTable<'_, 'post_query>],
+    conditions: Option<&conditional::Condition<'post_query>>,
+    order_by_clause: &[OrderByEntry<'_>],
+    limit: Option<LimitClause>,
+    key_column: usize,
+) -> Result<HashMap<K, V>, Error>
+where
+    K: DecodeOwned + Eq + Hash,
+    V: FromRow,
+{
+    query_fold(
+        executor,
+        model,
+        columns,
+        joins,
+        conditions,
+        order_by_clause,
+        limit,
+        HashMap::new(),
+        |mut map, row| {
+            let key = row.get(key_column)?;
+            map.insert(key, V::from_row(row)?);
+            Ok(map)
+        },
+    )
+    .await
+}
+
+/// Executes a simple `SELECT` query and lets Postgres aggregate the rows into a json array.
+///
+/// Each row is converted into a json object keyed by the selected columns (respecting their aliases)
+/// i.e. the query is wrapped in `SELECT json_agg(t) FROM (<query>) t`.
+/// If no rows match, an empty array is returned.
+///
+/// The parameters are the same as [`query`]'s for the [`All`] strategy.
+///
+/// Using this on any other database than Postgres returns an [`Error::ConfigurationError`].
+#[cfg(feature = "postgres")]
+pub async fn query_json<'post_query>(
+    executor: impl Executor<'_>,
+    model: &str,
+    columns: &[ColumnSelector<'_>],
+    joins: &[JoinTable<'_, 'post_query>],
+    conditions: Option<&conditional::Condition<'post_query>>,
+    order_by_clause: &[OrderByEntry<'_>],
+    limit: Option<LimitClause>,
+) -> Result<sqlx::types::JsonValue, Error> {
+    if !matches!(executor.dialect(), DBImpl::Postgres) {
+        return Err(Error::ConfigurationError(String::from(
+            "query_json is only supported on postgres",
+        )));
+    }
+
+    let (query_string, bind_params) = build_select(
+        executor.dialect(),
+        model,
+        columns,
+        joins,
+        conditions,
+        order_by_clause,
+        limit,
+    );
+    let query_string = format!(
+        "SELECT COALESCE(json_agg(t), '[]'::json) FROM ({}) t;",
+        query_string.trim_end().trim_end_matches(';')
+    );
+
+    debug!("SQL: {}", query_string);
+
+    let row = executor.execute::<One>(query_string, bind_params).await?;
+    let sqlx::types::Json(json) = row.get(0)?;
+    Ok(json)
+}
+
+/// Executes a Postgres `COPY ... TO STDOUT` statement and streams its raw output.
+///
+/// The chunks are passed on as they are received from the database,
+/// so they don't necessarily align with the rows.
+/// Their format is chosen by the statement's options,
+/// e.g. `COPY (SELECT * FROM "user") TO STDOUT WITH (FORMAT csv, HEADER)` produces csv.
+///
+/// The statement can't contain bind parameters.
+///
+/// Using this on any other database than Postgres returns an [`Error::ConfigurationError`].
+#[cfg(feature = "postgres")]
+pub fn copy_out<'executor>(
+    executor: impl Executor<'executor>,
+    statement: &str,
+) -> impl futures::Stream<Item = Result<Vec<u8>, Error>> + 'executor {
+    debug!("SQL: {}", statement);
+
+    internal::database::copy_out(executor.into_dyn(), statement.to_string())
+}
+
+/// Executes a simple `SELECT` query for the rows whose `column` equals any of `values`.
+///
+/// The comparison with `values` is combined with `conditions` using `AND`,
+/// so the placeholders are numbered consistently with the condition's values.
+/// Each value is compared using its own `column = ?`, joined by `OR`.
+/// An empty list of `values` matches no rows.
+///
+/// It is generic over a [`QueryStrategy`] which specifies how and how many rows to query.
+///
+/// **Parameter**:
+/// - `model`: Model to query.
+/// - `columns`: Columns to retrieve values from.
+/// - `joins`: Join tables expressions.
+/// - `conditions`: Optional conditions the rows have to match as well.
+/// - `order_by_clause`: Columns to order the rows by.
+/// - `limit`: Optional limit / offset to apply to the query, see [`query`].
+/// - `column`: Column of `model` to compare with `values`.
+/// - `values`: Values to compare `column` with.
+#[allow(clippy::too_many_arguments)]
+pub fn query_in<'result, 'db: 'result, 'post_query: 'result, Q: QueryStrategy + GetLimitClause>(
+    executor: impl Executor<'db>,
+    model: &'result str,
+    columns: &[ColumnSelector<'_>],
+    joins: &[JoinTable<'_, 'post_query>],
+    conditions: Option<&conditional::Condition<'post_query>>,
+    order_by_clause: &[OrderByEntry<'_>],
+    limit: Option<Q::LimitOrOffset>,
+    column: &'result str,
+    values: &[Value<'post_query>],
+) -> Q::Result<'result> {
+    let equals = |lhs, rhs| {
+        conditional::Condition::BinaryCondition(conditional::BinaryCondition::Equals(Box::new([
+            conditional::Condition::Value(lhs),
+            conditional::Condition::Value(rhs),
+        ])))
+    };
+    let matches_any = if values.is_empty() {
+        // An empty disjunction is not valid sql
+        equals(Value::I64(1), Value::I64(0))
+    } else {
+        let column = Value::Column {
+            table_name: Some(model),
+            column_name: column,
+        };
+        conditional::Condition::Disjunction(
+            values.iter().map(|value| equals(column, *value)).collect(),
+        )
+    };
+    let condition = match conditions {
+        Some(conditions) => {
+            conditional::Condition::Conjunction(vec![conditions.clone(), matches_any])
+        }
+        None => matches_any,
+    };
+
+    query::<Q>(
+        executor,
+        model,
+        columns,
+        joins,
+        Some(&condition),
+        order_by_clause,
+        limit,
+    )
+}
+
+/// Build the `SELECT` statement used by [`query`] and its variants
+fn build_select<'post_query>(
+    dialect: DBImpl,
+    model: &str,
+    columns: &[ColumnSelector<'_>],
+    joins: &[JoinTable<'_, 'post_query>],
+    conditions: Option<&conditional::Condition<'post_query>>,
+    order_by_clause: &[OrderByEntry<'_>],
+    limit: Option<LimitClause>,
+) -> (String, Vec<Value<'post_query>>) {
     let columns: Vec<_> = columns
         .iter()
-        .map(|c| {
-            executor.dialect().select_column(
-                c.table_name,
-                c.column_name,
-                c.select_alias,
-                c.aggregation,
-            )
-        })
+        .map(|c| dialect.select_column(c.table_name, c.column_name, c.select_alias, c.aggregation))
         .collect();
     let joins: Vec<_> = joins
         .iter()
-        .map(|j| {
-            executor
-                .dialect()
-                .join_table(j.join_type, j.table_name, j.join_alias, j.join_condition)
-        })
+        .map(|j| dialect.join_table(j.join_type, j.table_name, j.join_alias, j.join_condition))
         .collect();
-    let mut q = executor
-        .dialect()
-        .select(&columns, model, &joins, order_by_clause);
+    let mut q = dialect.select(&columns, model, &joins, order_by_clause);
 
     if let Some(condition) = conditions {
         q = q.where_clause(condition);
     }
 
-    if let Some(limit) = Q::get_limit_clause(limit) {
+    if let Some(limit) = limit {
         q = q.limit_clause(limit);
     }
 
-    let (query_string, bind_params) = q.build();
-
-    debug!("SQL: {}", query_string);
-
-    executor.execute::<Q>(query_string, bind_params)
+    q.build()
 }
 
 /// Inserts a single row and returns columns from it.
@@ -254,12 +1559,160 @@ pub async fn insert_returning(
     generic_insert::<One>(executor, model, columns, values, Some(returning)).await
 }
 
+/// Inserts a single row and returns columns from every row the insert produced.
+///
+/// Usually an insert produces exactly one row and [`insert_returning`] should be used.
+/// However, triggers (for example `INSTEAD OF` triggers or a partitioned table's routing trigger)
+/// can make a single insert return several rows, of which [`insert_returning`] only returns the first.
+///
+/// **Parameter**:
+/// - `model`: Table to insert to
+/// - `columns`: Columns to set `values` for.
+/// - `values`: Values to bind to the corresponding columns.
+/// - `returning`: Columns to query from the inserted rows.
+pub async fn insert_returning_all(
+    executor: impl Executor<'_>,
+    model: &str,
+    columns: &[&str],
+    values: &[Value<'_>],
+    returning: &[&str],
+) -> Result<Vec<Row>, Error> {
+    generic_insert::<All>(executor, model, columns, values, Some(returning)).await
+}
+
+/// Inserts a single row and returns computed expressions from it.
+///
+/// Unlike [`insert_returning`] whose `returning` are quoted column names,
+/// the `returning` expressions are inserted into the statement as raw SQL
+/// i.e. `RETURNING id, now() AS created_at` can be expressed as `&["id", "now() AS created_at"]`.
+/// Never pass untrusted input as expression.
+///
+/// Using this on MySQL returns an [`Error::ConfigurationError`], because it doesn't support `RETURNING`.
+///
+/// **Parameter**:
+/// - `model`: Table to insert to
+/// - `columns`: Columns to set `values` for.
+/// - `values`: Values to bind to the corresponding columns.
+/// - `returning`: SQL expressions to evaluate for the inserted row.
+pub async fn insert_returning_expressions(
+    executor: impl Executor<'_>,
+    model: &str,
+    columns: &[&str],
+    values: &[Value<'_>],
+    returning: &[&str],
+) -> Result<Row, Error> {
+    let dialect = executor.dialect();
+    #[cfg(feature = "mysql")]
+    if matches!(dialect, DBImpl::MySQL) {
+        return Err(Error::ConfigurationError(String::from(
+            "MySQL doesn't support RETURNING",
+        )));
+    }
+    if returning.is_empty() {
+        return Err(Error::ConfigurationError(String::from(
+            "returning requires at least one expression",
+        )));
+    }
+
+    let (query_string, bind_params) = dialect.insert(model, columns, &[values], None).build();
+    let query_string = format!(
+        "{} RETURNING {};",
+        query_string.trim_end().trim_end_matches(';'),
+        returning.join(", ")
+    );
+
+    debug!("SQL: {}", query_string);
+
+    executor.execute::<One>(query_string, bind_params).await
+}
+
+/// Inserts a single row and returns columns from it on every database.
+///
+/// Postgres and SQLite use `RETURNING` i.e. this is the same as [`insert_returning`].
+/// MySQL doesn't support `RETURNING`, so the row is selected again by its primary key
+/// in the same transaction as the insert.
+/// The primary key is taken from `values` if it is set explicitly,
+/// otherwise the auto increment id generated by the insert (`LAST_INSERT_ID()`) is used.
+///
+/// **Parameter**:
+/// - `model`: Table to insert to
+/// - `columns`: Columns to set `values` for.
+/// - `values`: Values to bind to the corresponding columns.
+/// - `returning`: Columns to query from the inserted row.
+/// - `primary_key`: Column identifying the inserted row on MySQL.
+#[cfg_attr(not(feature = "mysql"), allow(unused_variables))]
+pub async fn insert_returning_portable(
+    executor: impl Executor<'_>,
+    model: &str,
+    columns: &[&str],
+    values: &[Value<'_>],
+    returning: &[&str],
+    primary_key: &str,
+) -> Result<Row, Error> {
+    #[cfg(feature = "mysql")]
+    if matches!(executor.dialect(), DBImpl::MySQL) {
+        let mut guard = executor.ensure_transaction().await?;
+        let tr = &mut guard;
+
+        generic_insert::<Nothing>(&mut *tr, model, columns, values, None).await?;
+
+        let key = match columns.iter().position(|column| *column == primary_key) {
+            Some(index) => values[index].clone(),
+            None => {
+                // `LAST_INSERT_ID()` is unsigned which sqlx refuses to decode as `i64`
+                let row = tr
+                    .execute::<One>(
+                        String::from("SELECT CAST(LAST_INSERT_ID() AS SIGNED);"),
+                        Vec::new(),
+                    )
+                    .await?;
+                Value::I64(row.get(0)?)
+            }
+        };
+
+        let returning: Vec<_> = returning
+            .iter()
+            .map(|column| quote_identifier(DBImpl::MySQL, column))
+            .collect();
+        let query_string = format!(
+            "SELECT {} FROM {} WHERE {} = ?;",
+            returning.join(", "),
+            quote_identifier(DBImpl::MySQL, model),
+            quote_identifier(DBImpl::MySQL, primary_key),
+        );
+
+        debug!("SQL: {}", query_string);
+
+        let row = tr.execute::<One>(query_string, vec![key]).await?;
+        guard.commit().await?;
+        return Ok(row);
+    }
+
+    insert_returning(executor, model, columns, values, returning).await
+}
+
 /// Inserts a single row.
 ///
 /// **Parameter**:
 /// - `model`: Table to insert to
 /// - `columns`: Columns to set `values` for.
 /// - `values`: Values to bind to the corresponding columns.
+///
+/// # Owned values
+/// [`Value`] only borrows strings and bytes, but the borrow only has to outlive the returned future.
+/// Owned buffers like a `Vec<u8>` don't have to be kept alive elsewhere,
+/// moving them into the `async` block which awaits the insert is enough:
+///
+/// ```skipped
+/// let blob: Vec<u8> = read_upload().await?;
+/// tokio::spawn(async move {
+///     // `blob` is owned by this task and outlives the awaited insert
+///     database::insert(&db, "file", &["data"], &[Value::Binary(&blob)]).await
+/// });
+/// ```
+///
+/// The values are bound to the statement before it is sent,
+/// so `blob` can be dropped or reused as soon as the future has completed.
 pub async fn insert(
     executor: impl Executor<'_>,
     model: &str,
@@ -269,9 +1722,35 @@ pub async fn insert(
     generic_insert::<Nothing>(executor, model, columns, values, None).await
 }
 
+/// Inserts a single row using every column's default value.
+///
+/// **Parameter**:
+/// - `model`: Table to insert to
+pub async fn insert_default(executor: impl Executor<'_>, model: &str) -> Result<(), Error> {
+    let query_string = insert_default_sql(executor.dialect(), model);
+
+    debug!("SQL: {}", query_string);
+
+    executor.execute::<Nothing>(query_string, Vec::new()).await
+}
+
+/// Build the `INSERT` statement used by [`insert_default`]
+fn insert_default_sql(dialect: DBImpl, model: &str) -> String {
+    let model = quote_identifier(dialect, model);
+    match dialect {
+        #[cfg(feature = "mysql")]
+        DBImpl::MySQL => format!("INSERT INTO {model} () VALUES ();"),
+        #[cfg(feature = "postgres")]
+        DBImpl::Postgres => format!("INSERT INTO {model} DEFAULT VALUES;"),
+        #[cfg(feature = "sqlite")]
+        DBImpl::SQLite => format!("INSERT INTO {model} DEFAULT VALUES;"),
+    }
+}
+
 /// Generic implementation of:
 /// - [`Database::insert`]
 /// - [`Database::insert_returning`]
+/// - [`insert_returning_all`]
 pub(crate) fn generic_insert<'result, 'db: 'result, 'post_query: 'result, Q: QueryStrategy>(
     executor: impl Executor<'db>,
     model: &str,
@@ -291,7 +1770,8 @@ pub(crate) fn generic_insert<'result, 'db: 'result, 'post_query: 'result, Q: Que
 
 /// This method is used to bulk insert rows.
 ///
-/// If one insert statement fails, the complete operation will be rolled back.
+/// If one insert statement fails, the complete operation will be rolled back
+/// and an [`Error::BulkInsertError`] locating the failing rows is returned.
 ///
 /// **Parameter**:
 /// - `model`: Table to insert to
@@ -303,18 +1783,39 @@ pub async fn insert_bulk(
     model: &str,
     columns: &[&str],
     rows: &[&[Value<'_>]],
+) -> Result<(), Error> {
+    insert_bulk_with_progress(executor, model, columns, rows, |_| {}).await
+}
+
+/// Bulk inserts rows like [`insert_bulk`] while reporting its progress.
+///
+/// `progress` is called with the number of rows inserted so far after each chunk's statement succeeded.
+/// The rows only become visible to others once the transaction is committed after the last chunk,
+/// so the reported rows are still rolled back if a later chunk fails.
+pub async fn insert_bulk_with_progress(
+    executor: impl Executor<'_>,
+    model: &str,
+    columns: &[&str],
+    rows: &[&[Value<'_>]],
+    mut progress: impl FnMut(usize),
 ) -> Result<(), Error> {
     let mut guard = executor.ensure_transaction().await?;
-    let tr: &mut Transaction = guard.get_transaction();
+    let tr = &mut guard;
 
-    for chunk in rows.chunks(25) {
+    let chunk_size = bulk_chunk_size(tr, columns.len());
+    let mut inserted = 0;
+    for (index, chunk) in rows.chunks(chunk_size).enumerate() {
         let mut insert = tr.dialect().insert(model, columns, chunk, None);
         insert = insert.rollback_transaction();
         let (insert_query, insert_params) = insert.build();
 
         debug!("SQL: {}", insert_query);
 
-        tr.execute::<Nothing>(insert_query, insert_params).await?;
+        tr.execute::<Nothing>(insert_query, insert_params)
+            .await
+            .map_err(|error| bulk_insert_error(index, chunk_size, chunk.len(), error))?;
+        inserted += chunk.len();
+        progress(inserted);
     }
 
     guard.commit().await?;
@@ -323,7 +1824,8 @@ pub async fn insert_bulk(
 
 /// This method is used to bulk insert rows.
 ///
-/// If one insert statement fails, the complete operation will be rolled back.
+/// If one insert statement fails, the complete operation will be rolled back
+/// and an [`Error::BulkInsertError`] locating the failing rows is returned.
 ///
 /// **Parameter**:
 /// - `model`: Table to insert to
@@ -338,17 +1840,22 @@ pub async fn insert_bulk_returning(
     returning: &[&str],
 ) -> Result<Vec<Row>, Error> {
     let mut guard = executor.ensure_transaction().await?;
-    let tr: &mut Transaction = guard.get_transaction();
+    let tr = &mut guard;
 
     let mut inserted = Vec::with_capacity(rows.len());
-    for chunk in rows.chunks(25) {
+    let chunk_size = bulk_chunk_size(tr, columns.len());
+    for (index, chunk) in rows.chunks(chunk_size).enumerate() {
         let mut insert = tr.dialect().insert(model, columns, chunk, Some(returning));
         insert = insert.rollback_transaction();
         let (insert_query, insert_params) = insert.build();
 
         debug!("SQL: {}", insert_query);
 
-        inserted.extend(tr.execute::<All>(insert_query, insert_params).await?);
+        inserted.extend(
+            tr.execute::<All>(insert_query, insert_params)
+                .await
+                .map_err(|error| bulk_insert_error(index, chunk_size, chunk.len(), error))?,
+        );
     }
 
     guard.commit().await?;
@@ -356,6 +1863,621 @@ pub async fn insert_bulk_returning(
     Ok(inserted)
 }
 
+/// Bulk inserts rows like [`insert_bulk_returning`] but yields the returned rows as each chunk completes.
+///
+/// The rows are inserted in a transaction which is committed once the stream has been fully consumed.
+/// If an insert statement fails or the stream is dropped early, the complete operation will be rolled back.
+/// (When passing a transaction as executor, committing or rolling back is up to its owner.)
+///
+/// **Parameter**:
+/// - `model`: Table to insert to
+/// - `columns`: Columns to set `rows` for.
+/// - `rows`: List of values to bind to the corresponding columns.
+/// - `returning`: Columns to query from the inserted rows.
+pub fn insert_bulk_returning_stream<'result, 'db: 'result, 'rows: 'result>(
+    executor: impl Executor<'db> + 'result,
+    model: &'result str,
+    columns: &'result [&'result str],
+    rows: &'result [&'result [Value<'rows>]],
+    returning: &'result [&'result str],
+) -> impl futures::Stream<Item = Result<Row, Error>> + 'result {
+    enum State<'tr, E, C> {
+        Start(E),
+        Running {
+            guard: TransactionGuard<'tr>,
+            chunk_size: usize,
+            chunks: C,
+            buffered: std::vec::IntoIter<Row>,
+        },
+        Done,
+    }
+
+    // Errors transition to `State::Done` which drops the guard and thereby rolls back
+    futures::stream::unfold(State::Start(executor), move |state| async move {
+        let (mut guard, chunk_size, mut chunks, mut buffered) = match state {
+            State::Start(executor) => match executor.ensure_transaction().await {
+                Ok(mut guard) => {
+                    let chunk_size = bulk_chunk_size(&mut guard, columns.len());
+                    (
+                        guard,
+                        chunk_size,
+                        rows.chunks(chunk_size).enumerate(),
+                        Vec::new().into_iter(),
+                    )
+                }
+                Err(error) => return Some((Err(error), State::Done)),
+            },
+            State::Running {
+                guard,
+                chunk_size,
+                chunks,
+                buffered,
+            } => (guard, chunk_size, chunks, buffered),
+            State::Done => return None,
+        };
+
+        loop {
+            if let Some(row) = buffered.next() {
+                let state = State::Running {
+                    guard,
+                    chunk_size,
+                    chunks,
+                    buffered,
+                };
+                return Some((Ok(row), state));
+            }
+
+            let Some((index, chunk)) = chunks.next() else {
+                return match guard.commit().await {
+                    Ok(()) => None,
+                    Err(error) => Some((Err(error), State::Done)),
+                };
+            };
+
+            let tr = &mut guard;
+            let mut insert = tr.dialect().insert(model, columns, chunk, Some(returning));
+            insert = insert.rollback_transaction();
+            let (insert_query, insert_params) = insert.build();
+
+            debug!("SQL: {}", insert_query);
+
+            match tr.execute::<All>(insert_query, insert_params).await {
+                Ok(inserted) => buffered = inserted.into_iter(),
+                Err(error) => {
+                    let error = bulk_insert_error(index, chunk_size, chunk.len(), error);
+                    return Some((Err(error), State::Done));
+                }
+            }
+        }
+    })
+}
+
+/// Number of rows inserted by a single statement in [`insert_bulk`] and [`insert_bulk_returning`]
+const BULK_CHUNK_SIZE: usize = 25;
+
+/// All statements that take longer to execute than this value are considered
+/// as slow statements, unless [`DatabaseConfiguration::slow_statement_threshold`] is set.
+const SLOW_STATEMENTS: Duration = Duration::from_millis(300);
+
+/// Number of rows a bulk insert on `guard` inserts per statement
+///
+/// It is [`BULK_CHUNK_SIZE`] unless the rows have too many columns
+/// to stay below [`DatabaseConfiguration::max_bind_parameters`].
+fn bulk_chunk_size(guard: &mut TransactionGuard<'_>, columns: usize) -> usize {
+    let max_bind_parameters = match guard.try_get_transaction() {
+        Some(tr) => tr.2.max_bind_parameters(tr.dialect()),
+        None => default_max_bind_parameters(guard.dialect()),
+    };
+    (max_bind_parameters / columns.max(1)).clamp(1, BULK_CHUNK_SIZE)
+}
+
+/// Wrap an error of a bulk insert's chunk with its position
+fn bulk_insert_error(chunk: usize, chunk_size: usize, chunk_len: usize, source: Error) -> Error {
+    let start = chunk * chunk_size;
+    Error::BulkInsertError {
+        chunk,
+        rows: start..start + chunk_len,
+        source: Box::new(source),
+    }
+}
+
+/// Inserts a single row or updates the existing one if it violates a unique constraint.
+///
+/// **Parameter**:
+/// - `model`: Table to insert to
+/// - `columns`: Columns to set `values` for.
+/// - `values`: Values to bind to the corresponding columns.
+/// - `conflict_columns`: Columns of the unique index whose violation triggers the update.
+///   MySQL ignores them and checks every unique index.
+/// - `conflict_predicate`: Optional raw SQL predicate selecting a partial unique index
+///   i.e. `ON CONFLICT (...) WHERE <predicate>`. It is not supported on MySQL.
+/// - `update_columns`: Columns to overwrite with the new values.
+///   If empty, the existing row is left unchanged.
+///
+/// **Returns** the rows affected by the statement.
+pub async fn insert_or_update(
+    executor: impl Executor<'_>,
+    model: &str,
+    columns: &[&str],
+    values: &[Value<'_>],
+    conflict_columns: &[&str],
+    conflict_predicate: Option<&str>,
+    update_columns: &[&str],
+) -> Result<u64, Error> {
+    let query_string = insert_or_update_sql(
+        executor.dialect(),
+        model,
+        columns,
+        conflict_columns,
+        conflict_predicate,
+        update_columns,
+        None,
+    )?;
+
+    debug!("SQL: {}", query_string);
+
+    executor
+        .execute::<AffectedRows>(query_string, values.to_vec())
+        .await
+}
+
+/// Inserts a single row or updates the existing one like [`insert_or_update`] and returns columns from it.
+///
+/// Postgres and SQLite append `RETURNING` to the statement.
+/// MySQL doesn't support `RETURNING`, so the row is selected again by its `conflict_columns`
+/// in the same transaction as the upsert.
+/// Therefore MySQL requires the `conflict_columns` to be set and to be part of `columns`.
+///
+/// **Parameter**:
+/// - `returning`: Columns to query from the inserted or updated row.
+/// - The remaining parameters are the same as [`insert_or_update`]'s.
+///
+/// **Returns** `None` if the row already existed and `update_columns` is empty,
+/// i.e. nothing has been inserted or updated.
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_or_update_returning(
+    executor: impl Executor<'_>,
+    model: &str,
+    columns: &[&str],
+    values: &[Value<'_>],
+    conflict_columns: &[&str],
+    conflict_predicate: Option<&str>,
+    update_columns: &[&str],
+    returning: &[&str],
+) -> Result<Option<Row>, Error> {
+    let dialect = executor.dialect();
+    let query_string = insert_or_update_sql(
+        dialect,
+        model,
+        columns,
+        conflict_columns,
+        conflict_predicate,
+        update_columns,
+        Some(returning),
+    )?;
+
+    #[cfg(feature = "mysql")]
+    if matches!(dialect, DBImpl::MySQL) {
+        if conflict_columns.is_empty() {
+            return Err(Error::ConfigurationError(String::from(
+                "MySQL requires conflict_columns to select the upserted row",
+            )));
+        }
+        let mut conflict_values = Vec::with_capacity(conflict_columns.len());
+        for conflict_column in conflict_columns {
+            match columns.iter().position(|column| column == conflict_column) {
+                Some(index) => conflict_values.push(values[index].clone()),
+                None => {
+                    return Err(Error::ConfigurationError(format!(
+                        "the conflict column {conflict_column} has to be inserted to select the upserted row on MySQL"
+                    )))
+                }
+            }
+        }
+
+        let quote = |identifier: &&str| quote_identifier(DBImpl::MySQL, identifier);
+        let select = format!(
+            "SELECT {} FROM {} WHERE {}",
+            returning.iter().map(quote).collect::<Vec<_>>().join(", "),
+            quote(&model),
+            conflict_columns
+                .iter()
+                .map(|column| format!("{} = ?", quote(column)))
+                .collect::<Vec<_>>()
+                .join(" AND "),
+        );
+
+        let mut guard = executor.ensure_transaction().await?;
+        let tr = &mut guard;
+
+        if update_columns.is_empty() {
+            // MySQL reports the same affected rows for an insert and an ignored conflict
+            let query = format!("{select} FOR UPDATE;");
+            debug!("SQL: {}", query);
+            if tr
+                .execute::<Optional>(query, conflict_values.clone())
+                .await?
+                .is_some()
+            {
+                guard.commit().await?;
+                return Ok(None);
+            }
+        }
+
+        debug!("SQL: {}", query_string);
+        tr.execute::<Nothing>(query_string, values.to_vec()).await?;
+
+        let query = format!("{select};");
+        debug!("SQL: {}", query);
+        let row = tr.execute::<One>(query, conflict_values).await?;
+
+        guard.commit().await?;
+        return Ok(Some(row));
+    }
+
+    debug!("SQL: {}", query_string);
+
+    executor
+        .execute::<Optional>(query_string, values.to_vec())
+        .await
+}
+
+/// Action taken by [`merge`] for a source row matching an existing row
+#[derive(Copy, Clone, Debug)]
+pub enum WhenMatched<'a> {
+    /// Leave the existing row unchanged
+    DoNothing,
+    /// Overwrite the listed columns with the source row's values
+    Update(&'a [&'a str]),
+    /// Delete the existing row
+    Delete,
+}
+
+/// Action taken by [`merge`] for a source row without a matching row
+#[derive(Copy, Clone, Debug)]
+pub enum WhenNotMatched {
+    /// Skip the source row
+    DoNothing,
+    /// Insert the source row
+    Insert,
+}
+
+/// Merges rows into a table deciding per row whether to update, delete or insert.
+///
+/// Postgres (15 or newer) executes a `MERGE` statement
+/// whose source is a `VALUES` list of the `source_rows`.
+/// A row of the table must not be matched by more than one source row.
+///
+/// MySQL and SQLite don't support `MERGE`, so the rows are upserted one by one
+/// like [`insert_or_update`] with `match_columns` as conflict columns:
+/// - [`WhenNotMatched::Insert`] combined with [`WhenMatched::DoNothing`] or [`WhenMatched::Update`] are supported.
+/// - [`WhenMatched::DoNothing`] combined with [`WhenNotMatched::DoNothing`] does nothing.
+/// - Every other combination returns an [`Error::ConfigurationError`].
+///
+/// This requires a unique index over the `match_columns` on MySQL and SQLite.
+///
+/// All statements are executed in a single transaction.
+///
+/// **Parameter**:
+/// - `target`: Table to merge into
+/// - `columns`: Columns to set `source_rows` for.
+/// - `source_rows`: List of values to bind to the corresponding columns.
+/// - `match_columns`: Columns comparing a source row with the table's rows.
+///   They have to be contained in `columns`.
+/// - `when_matched`: Action for source rows matching an existing row.
+/// - `when_not_matched`: Action for source rows without a matching row.
+///
+/// **Returns** the rows affected by the statements.
+pub async fn merge(
+    executor: impl Executor<'_>,
+    target: &str,
+    columns: &[&str],
+    source_rows: &[&[Value<'_>]],
+    match_columns: &[&str],
+    when_matched: WhenMatched<'_>,
+    when_not_matched: WhenNotMatched,
+) -> Result<u64, Error> {
+    if match_columns.is_empty() || !match_columns.iter().all(|column| columns.contains(column)) {
+        return Err(Error::ConfigurationError(String::from(
+            "a merge requires match_columns which are contained in columns",
+        )));
+    }
+    if let WhenMatched::Update([]) = when_matched {
+        return Err(Error::ConfigurationError(String::from(
+            "a merge's update requires at least one column",
+        )));
+    }
+
+    let dialect = executor.dialect();
+    // The upsert's update columns if the dialect doesn't support MERGE
+    let upsert_columns = match dialect {
+        #[cfg(feature = "postgres")]
+        DBImpl::Postgres => None,
+        #[allow(unreachable_patterns)]
+        _ => match (when_matched, when_not_matched) {
+            (WhenMatched::DoNothing, WhenNotMatched::Insert) => Some(&[][..]),
+            (WhenMatched::Update(update_columns), WhenNotMatched::Insert) => Some(update_columns),
+            (WhenMatched::DoNothing, WhenNotMatched::DoNothing) => return Ok(0),
+            _ => {
+                return Err(Error::ConfigurationError(format!(
+                    "MERGE is only supported on postgres and an upsert can't express \
+                    {when_matched:?} when matched combined with {when_not_matched:?} when not matched"
+                )))
+            }
+        },
+    };
+
+    let mut guard = executor.ensure_transaction().await?;
+    let tr = &mut guard;
+
+    let mut affected = 0;
+    match upsert_columns {
+        Some(update_columns) => {
+            let query_string = insert_or_update_sql(
+                dialect,
+                target,
+                columns,
+                match_columns,
+                None,
+                update_columns,
+                None,
+            )?;
+            for row in source_rows {
+                debug!("SQL: {}", query_string);
+                affected += tr
+                    .execute::<AffectedRows>(query_string.clone(), row.to_vec())
+                    .await?;
+            }
+        }
+        #[cfg(feature = "postgres")]
+        None => {
+            let chunk_size = bulk_chunk_size(tr, columns.len());
+            for chunk in source_rows.chunks(chunk_size) {
+                let query_string = merge_sql(
+                    target,
+                    columns,
+                    chunk.len(),
+                    match_columns,
+                    when_matched,
+                    when_not_matched,
+                );
+                debug!("SQL: {}", query_string);
+                let values = chunk.iter().flat_map(|row| row.iter().cloned()).collect();
+                affected += tr.execute::<AffectedRows>(query_string, values).await?;
+            }
+        }
+        #[cfg(not(feature = "postgres"))]
+        None => unreachable!("only postgres supports MERGE"),
+    }
+
+    guard.commit().await?;
+    Ok(affected)
+}
+
+/// Build postgres' `MERGE` statement used by [`merge`] for `rows` source rows
+#[cfg(feature = "postgres")]
+fn merge_sql(
+    target: &str,
+    columns: &[&str],
+    rows: usize,
+    match_columns: &[&str],
+    when_matched: WhenMatched<'_>,
+    when_not_matched: WhenNotMatched,
+) -> String {
+    let quote = |identifier: &&str| quote_identifier(DBImpl::Postgres, identifier);
+    let column_list = columns.iter().map(quote).collect::<Vec<_>>().join(", ");
+    let source_values = (0..rows)
+        .map(|row| {
+            let placeholders = (1..=columns.len())
+                .map(|column| placeholder(DBImpl::Postgres, row * columns.len() + column))
+                .collect::<Vec<_>>();
+            format!("({})", placeholders.join(", "))
+        })
+        .collect::<Vec<_>>();
+    let condition = match_columns
+        .iter()
+        .map(|column| {
+            let column = quote(column);
+            format!("t.{column} = s.{column}")
+        })
+        .collect::<Vec<_>>();
+
+    let mut query = format!(
+        "MERGE INTO {} AS t USING (VALUES {}) AS s ({column_list}) ON {}",
+        quote(&target),
+        source_values.join(", "),
+        condition.join(" AND "),
+    );
+    match when_matched {
+        WhenMatched::DoNothing => query.push_str(" WHEN MATCHED THEN DO NOTHING"),
+        WhenMatched::Update(update_columns) => {
+            let updates = update_columns
+                .iter()
+                .map(|column| {
+                    let column = quote(column);
+                    format!("{column} = s.{column}")
+                })
+                .collect::<Vec<_>>();
+            query.push_str(" WHEN MATCHED THEN UPDATE SET ");
+            query.push_str(&updates.join(", "));
+        }
+        WhenMatched::Delete => query.push_str(" WHEN MATCHED THEN DELETE"),
+    }
+    match when_not_matched {
+        WhenNotMatched::DoNothing => query.push_str(" WHEN NOT MATCHED THEN DO NOTHING"),
+        WhenNotMatched::Insert => {
+            let source_columns = columns
+                .iter()
+                .map(|column| format!("s.{}", quote(column)))
+                .collect::<Vec<_>>();
+            query.push_str(&format!(
+                " WHEN NOT MATCHED THEN INSERT ({column_list}) VALUES ({})",
+                source_columns.join(", ")
+            ));
+        }
+    }
+    query.push(';');
+    query
+}
+
+/// Build the statement used by [`insert_or_update`] and [`insert_or_update_returning`]
+///
+/// MySQL ignores `returning`.
+#[cfg_attr(
+    not(any(feature = "postgres", feature = "sqlite")),
+    allow(unused_variables)
+)]
+fn insert_or_update_sql(
+    dialect: DBImpl,
+    model: &str,
+    columns: &[&str],
+    conflict_columns: &[&str],
+    conflict_predicate: Option<&str>,
+    update_columns: &[&str],
+    returning: Option<&[&str]>,
+) -> Result<String, Error> {
+    if columns.is_empty() {
+        return Err(Error::ConfigurationError(String::from(
+            "an upsert requires at least one column",
+        )));
+    }
+
+    let quote = |identifier: &&str| quote_identifier(dialect, identifier);
+    let insert = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        quote(&model),
+        columns.iter().map(quote).collect::<Vec<_>>().join(", "),
+        (1..=columns.len())
+            .map(|index| placeholder(dialect, index))
+            .collect::<Vec<_>>()
+            .join(", "),
+    );
+
+    // Postgres and SQLite share the same syntax
+    #[cfg(any(feature = "postgres", feature = "sqlite"))]
+    let on_conflict = || {
+        let returning = match returning {
+            Some(returning) => format!(
+                " RETURNING {}",
+                returning.iter().map(quote).collect::<Vec<_>>().join(", ")
+            ),
+            None => String::new(),
+        };
+
+        let mut target = String::new();
+        if !conflict_columns.is_empty() {
+            target = format!(
+                " ({})",
+                conflict_columns
+                    .iter()
+                    .map(quote)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            if let Some(predicate) = conflict_predicate {
+                target = format!("{target} WHERE {predicate}");
+            }
+        } else if conflict_predicate.is_some() || !update_columns.is_empty() {
+            return Err(Error::ConfigurationError(String::from(
+                "conflict_columns are required to use a conflict predicate or to update on conflict",
+            )));
+        }
+
+        if update_columns.is_empty() {
+            Ok(format!(
+                "{insert} ON CONFLICT{target} DO NOTHING{returning};"
+            ))
+        } else {
+            let updates = update_columns
+                .iter()
+                .map(|column| {
+                    let column = quote(column);
+                    format!("{column} = EXCLUDED.{column}")
+                })
+                .collect::<Vec<_>>();
+            Ok(format!(
+                "{insert} ON CONFLICT{target} DO UPDATE SET {}{returning};",
+                updates.join(", ")
+            ))
+        }
+    };
+
+    match dialect {
+        #[cfg(feature = "mysql")]
+        DBImpl::MySQL => {
+            if conflict_predicate.is_some() {
+                return Err(Error::ConfigurationError(String::from(
+                    "MySQL doesn't support conflict predicates",
+                )));
+            }
+            let updates = if update_columns.is_empty() {
+                // Assigning a column to itself is MySQL's way of "DO NOTHING"
+                let column = quote(&columns[0]);
+                vec![format!("{column} = {column}")]
+            } else {
+                update_columns
+                    .iter()
+                    .map(|column| {
+                        let column = quote(column);
+                        format!("{column} = VALUES({column})")
+                    })
+                    .collect()
+            };
+            Ok(format!(
+                "{insert} ON DUPLICATE KEY UPDATE {};",
+                updates.join(", ")
+            ))
+        }
+        #[cfg(feature = "postgres")]
+        DBImpl::Postgres => on_conflict(),
+        #[cfg(feature = "sqlite")]
+        DBImpl::SQLite => on_conflict(),
+    }
+}
+
+/// Get the placeholder for the parameter at (1-based) `index` in a sql dialect
+pub(crate) fn placeholder(dialect: DBImpl, index: usize) -> String {
+    match dialect {
+        #[cfg(feature = "postgres")]
+        DBImpl::Postgres => format!("${index}"),
+        #[cfg(feature = "mysql")]
+        DBImpl::MySQL => String::from("?"),
+        #[cfg(feature = "sqlite")]
+        DBImpl::SQLite => String::from("?"),
+    }
+}
+
+/// Executes the same query once for every set of parameters.
+///
+/// All executions happen inside a single transaction.
+/// Since the query string doesn't change, the prepared statement is reused for every execution.
+///
+/// **Parameter**:
+/// - `query`: SQL statement to execute.
+/// - `param_sets`: List of values to bind in each execution.
+///
+/// **Returns** the sum of the rows affected by every execution.
+pub async fn execute_many(
+    executor: impl Executor<'_>,
+    query: &str,
+    param_sets: &[Vec<Value<'_>>],
+) -> Result<u64, Error> {
+    let mut guard = executor.ensure_transaction().await?;
+    let tr = &mut guard;
+
+    let mut affected = 0;
+    for params in param_sets {
+        debug!("SQL: {}", query);
+
+        affected += tr
+            .execute::<AffectedRows>(query.to_string(), params.clone())
+            .await?;
+    }
+
+    guard.commit().await?;
+    Ok(affected)
+}
+
 /// This method is used to delete rows from a table.
 ///
 /// **Parameter**:
@@ -384,6 +2506,94 @@ pub async fn delete<'post_build>(
         .await
 }
 
+/// Removes all rows from a table.
+///
+/// - Postgres executes `TRUNCATE` with `RESTART IDENTITY` and `CASCADE` as requested.
+/// - MySQL executes `TRUNCATE TABLE` which always resets the `AUTO_INCREMENT` counter,
+///   so it falls back to `DELETE FROM` if `restart_identity` is false.
+///   Note that `TRUNCATE TABLE` implicitly commits an open transaction.
+///   `cascade` isn't supported and returns an [`Error::ConfigurationError`].
+/// - SQLite has no `TRUNCATE` and executes `DELETE FROM` instead.
+///   With `restart_identity`, the table's `AUTOINCREMENT` counter is removed from `sqlite_sequence`.
+///   `cascade` is ignored, referencing rows are only removed by their foreign keys' `ON DELETE CASCADE`.
+///
+/// **Parameter**:
+/// - `model`: Name of the table to clear
+/// - `restart_identity`: Whether to reset the table's auto-incrementing columns.
+/// - `cascade`: Whether to clear the tables referencing this one as well.
+#[cfg_attr(
+    not(any(feature = "postgres", feature = "mysql")),
+    allow(unused_variables)
+)]
+pub async fn truncate(
+    executor: impl Executor<'_>,
+    model: &str,
+    restart_identity: bool,
+    cascade: bool,
+) -> Result<(), Error> {
+    let dialect = executor.dialect();
+    let table = quote_identifier(dialect, model);
+    let query_string = match dialect {
+        #[cfg(feature = "postgres")]
+        DBImpl::Postgres => format!(
+            "TRUNCATE {table}{}{};",
+            if restart_identity {
+                " RESTART IDENTITY"
+            } else {
+                ""
+            },
+            if cascade { " CASCADE" } else { "" },
+        ),
+        #[cfg(feature = "mysql")]
+        DBImpl::MySQL => {
+            if cascade {
+                return Err(Error::ConfigurationError(String::from(
+                    "MySQL doesn't support truncating with cascade",
+                )));
+            }
+            if restart_identity {
+                format!("TRUNCATE TABLE {table};")
+            } else {
+                format!("DELETE FROM {table};")
+            }
+        }
+        #[cfg(feature = "sqlite")]
+        DBImpl::SQLite => {
+            let mut guard = executor.ensure_transaction().await?;
+            let tr = &mut guard;
+
+            let query_string = format!("DELETE FROM {table};");
+            debug!("SQL: {}", query_string);
+            tr.execute::<Nothing>(query_string, Vec::new()).await?;
+
+            if restart_identity {
+                // sqlite_sequence only exists once any table uses AUTOINCREMENT
+                let query_string = String::from(
+                    "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'sqlite_sequence';",
+                );
+                debug!("SQL: {}", query_string);
+                if tr
+                    .execute::<Optional>(query_string, Vec::new())
+                    .await?
+                    .is_some()
+                {
+                    let query_string = String::from("DELETE FROM sqlite_sequence WHERE name = ?;");
+                    debug!("SQL: {}", query_string);
+                    tr.execute::<Nothing>(query_string, vec![Value::String(model)])
+                        .await?;
+                }
+            }
+
+            guard.commit().await?;
+            return Ok(());
+        }
+    };
+
+    debug!("SQL: {}", query_string);
+
+    executor.execute::<Nothing>(query_string, Vec::new()).await
+}
+
 /// This method is used to update rows in a table.
 ///
 /// **Parameter**:
@@ -431,4 +2641,47 @@ mod test {
         let fut: BoxFuture<'_, Result<Vec<Row>, Error>> = Box::pin(fut);
         drop(fut);
     }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn test_debug_redacts_password() {
+        use rorm_declaration::config::DatabaseDriver;
+
+        use crate::DatabaseConfiguration;
+
+        let configuration = DatabaseConfiguration::new(DatabaseDriver::Postgres {
+            name: String::from("db"),
+            host: String::from("localhost"),
+            port: 5432,
+            user: String::from("user"),
+            password: String::from("hunter2"),
+        });
+        let debug = format!("{:?}", configuration.clone());
+        assert!(!debug.contains("hunter2"), "{debug}");
+        assert!(debug.contains("<redacted>"), "{debug}");
+    }
+
+    #[cfg(all(feature = "sqlite", feature = "tokio"))]
+    #[tokio::test]
+    async fn test_dry_run_insert_bulk() {
+        use rorm_sql::value::Value;
+        use rorm_sql::DBImpl;
+
+        use crate::executor::DryRunExecutor;
+
+        let dry_run = DryRunExecutor::new(DBImpl::SQLite);
+        database::insert_bulk(
+            &dry_run,
+            "user",
+            &["name"],
+            &[&[Value::String("alice")], &[Value::String("bob")]],
+        )
+        .await
+        .unwrap();
+
+        let statements = dry_run.take_statements();
+        assert_eq!(statements.len(), 1);
+        assert!(statements[0].query.starts_with("INSERT"));
+        assert_eq!(statements[0].values.len(), 2);
+    }
 }
@@ -0,0 +1,22 @@
+use std::future::Future;
+
+use super::NotInstantiable;
+use crate::connection::Connection;
+
+pub(crate) type Impl = NotInstantiable;
+
+/// Used by [AdvisoryLock::drop](crate::database::AdvisoryLock) to release the lock in the background
+pub fn spawn<F>(
+    connection: Connection,
+    _task: impl FnOnce(Connection) -> F,
+) -> Result<(), Connection>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    match connection.0 {}
+}
+
+/// Closes `connection` instead of returning it to the pool
+pub fn close(connection: Impl) {
+    match connection {}
+}
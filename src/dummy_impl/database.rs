@@ -1,18 +1,41 @@
+use std::path::Path;
+use std::time::Duration;
+
 use rorm_sql::value::Value;
 
-use super::{no_sqlx, NotInstantiable};
-use crate::database::{Database, DatabaseConfiguration};
+use super::{no_sqlx, no_sqlx_result, NotInstantiable};
+use crate::database::{Database, DatabaseConfiguration, IsolationLevel};
 use crate::error::Error;
+use crate::internal;
 use crate::row::Row;
 use crate::transaction::Transaction;
 
 pub(crate) type Impl = NotInstantiable;
 
 /// Implementation of [Database::connect]
-pub(crate) async fn connect(_configuration: DatabaseConfiguration) -> Result<Impl, Error> {
+pub(crate) async fn connect(_configuration: &DatabaseConfiguration) -> Result<Impl, Error> {
+    no_sqlx_result()
+}
+
+/// Checked in [Database::connect_with_retry] to decide whether to retry
+pub fn is_transient(_error: &Error) -> bool {
+    if cfg!(feature = "dummy-returns-error") {
+        false
+    } else {
+        no_sqlx();
+    }
+}
+
+/// Used by [Database::connect_with_retry] to wait between attempts
+pub async fn sleep(_duration: Duration) {
     no_sqlx();
 }
 
+/// Used by [Database::execute_file] to read the script without blocking the runtime
+pub async fn read_to_string(_path: &Path) -> Result<String, Error> {
+    no_sqlx_result()
+}
+
 /// Implementation of [Database::raw_sql]
 pub async fn raw_sql<'a>(
     db: &Database,
@@ -22,12 +45,35 @@ pub async fn raw_sql<'a>(
 ) -> Result<Vec<Row>, Error> {
     // "Read" pool at least once
     let _ = db.0;
-    no_sqlx();
+    no_sqlx_result()
+}
+
+/// Implementation of [Database::execute_unprepared]
+pub async fn execute_unprepared(_db: &Database, _sql: &str) -> Result<u64, Error> {
+    no_sqlx_result()
+}
+
+/// Implementation of [Database::execute_batch]
+pub async fn execute_batch(_db: &Database, _sql: &str) -> Result<(), Error> {
+    no_sqlx_result()
+}
+
+/// Implementation of [Database::prepare]
+pub async fn prepare(_db: &Database, _sql: &str) -> Result<(), Error> {
+    no_sqlx_result()
 }
 
 /// Implementation of [Database::start_transaction]
-pub async fn start_transaction(_db: &Database) -> Result<Transaction, Error> {
-    no_sqlx();
+pub async fn start_transaction(
+    _db: &Database,
+    _isolation_level: Option<IsolationLevel>,
+) -> Result<internal::transaction::Impl, Error> {
+    no_sqlx_result()
+}
+
+/// Implementation of [Database::with_connection]
+pub async fn acquire(_db: &Database) -> Result<internal::connection::Impl, Error> {
+    no_sqlx_result()
 }
 
 /// Implementation of [Database::close]
@@ -35,6 +81,11 @@ pub async fn close(db: Database) {
     no_sqlx();
 }
 
+/// Implementation of [Database::drain_complete]
+pub async fn drain(_db: &Database) {
+    no_sqlx();
+}
+
 /// Checked in [Database::drop]
 pub fn is_closed(db: &Database) -> bool {
     no_sqlx();
@@ -1,16 +1,17 @@
-use std::future::Ready;
+use std::future::{ready, Ready};
 
 use futures::future::BoxFuture;
-use futures::stream::Empty;
+use futures::stream::{self, Once};
 use rorm_sql::value::Value;
 use rorm_sql::DBImpl;
 
-use super::no_sqlx;
+use super::{no_sqlx, no_sqlx_result};
+use crate::connection::Connection;
 use crate::database::Database;
 use crate::error::Error;
 use crate::executor::{
-    AffectedRows, All, DynamicExecutor, Executor, Nothing, One, Optional, QueryStrategy,
-    QueryStrategyResult, Stream,
+    AffectedRows, All, AllWithAffected, AtMostOne, DryRunExecutor, DynamicExecutor, ExactlyOne,
+    Executor, MultiResultSet, Nothing, One, Optional, QueryStrategy, QueryStrategyResult, Stream,
 };
 use crate::row::Row;
 use crate::transaction::{Transaction, TransactionGuard};
@@ -26,7 +27,7 @@ impl<'executor> Executor<'executor> for &'executor Database {
         'data: 'result,
         Q: QueryStrategy,
     {
-        no_sqlx();
+        Q::no_sqlx()
     }
 
     fn into_dyn(self) -> DynamicExecutor<'executor> {
@@ -42,7 +43,7 @@ impl<'executor> Executor<'executor> for &'executor Database {
     fn ensure_transaction(
         self,
     ) -> BoxFuture<'executor, Result<TransactionGuard<'executor>, Error>> {
-        no_sqlx();
+        Box::pin(ready(no_sqlx_result()))
     }
 }
 
@@ -57,7 +58,7 @@ impl<'executor> Executor<'executor> for &'executor mut Transaction {
         'data: 'result,
         Q: QueryStrategy,
     {
-        no_sqlx();
+        Q::no_sqlx()
     }
 
     fn into_dyn(self) -> DynamicExecutor<'executor> {
@@ -73,13 +74,105 @@ impl<'executor> Executor<'executor> for &'executor mut Transaction {
     fn ensure_transaction(
         self,
     ) -> BoxFuture<'executor, Result<TransactionGuard<'executor>, Error>> {
+        Box::pin(ready(no_sqlx_result()))
+    }
+}
+
+impl<'executor> Executor<'executor> for &'executor mut Connection {
+    fn execute<'data, 'result, Q>(
+        self,
+        _query: String,
+        _values: Vec<Value<'data>>,
+    ) -> Q::Result<'result>
+    where
+        'executor: 'result,
+        'data: 'result,
+        Q: QueryStrategy,
+    {
+        Q::no_sqlx()
+    }
+
+    fn into_dyn(self) -> DynamicExecutor<'executor> {
+        DynamicExecutor::Connection(self)
+    }
+
+    fn dialect(&self) -> DBImpl {
         no_sqlx();
     }
+
+    type EnsureTransactionFuture = Ready<Result<TransactionGuard<'executor>, Error>>;
+
+    fn ensure_transaction(
+        self,
+    ) -> BoxFuture<'executor, Result<TransactionGuard<'executor>, Error>> {
+        Box::pin(ready(no_sqlx_result()))
+    }
 }
 
-pub trait QueryStrategyImpl: QueryStrategyResult {}
+impl<'executor> Executor<'executor> for &'executor DryRunExecutor {
+    fn execute<'data, 'result, Q>(
+        self,
+        _query: String,
+        _values: Vec<Value<'data>>,
+    ) -> Q::Result<'result>
+    where
+        'executor: 'result,
+        'data: 'result,
+        Q: QueryStrategy,
+    {
+        Q::no_sqlx()
+    }
+
+    fn into_dyn(self) -> DynamicExecutor<'executor> {
+        DynamicExecutor::DryRun(self)
+    }
+
+    fn dialect(&self) -> DBImpl {
+        self.dialect
+    }
+
+    type EnsureTransactionFuture = Ready<Result<TransactionGuard<'executor>, Error>>;
+
+    fn ensure_transaction(
+        self,
+    ) -> BoxFuture<'executor, Result<TransactionGuard<'executor>, Error>> {
+        Box::pin(ready(Ok(TransactionGuard::DryRun(self))))
+    }
+}
 
-impl<Q: QueryStrategyResult> QueryStrategyImpl for Q {}
+pub trait QueryStrategyImpl: QueryStrategyResult {
+    /// The result of executing a query without sqlx
+    ///
+    /// See [`no_sqlx_result`]
+    fn no_sqlx<'result>() -> Self::Result<'result>;
+}
+
+macro_rules! impl_query_strategy_with_ready_result {
+    ($($strategy:ty),+) => {$(
+        impl QueryStrategyImpl for $strategy {
+            fn no_sqlx<'result>() -> Self::Result<'result> {
+                ready(no_sqlx_result())
+            }
+        }
+    )+};
+}
+impl_query_strategy_with_ready_result!(
+    Nothing,
+    AffectedRows,
+    One,
+    Optional,
+    ExactlyOne,
+    AtMostOne,
+    All,
+    AllWithAffected,
+    MultiResultSet
+);
+
+impl QueryStrategyImpl for Stream {
+    fn no_sqlx<'result>() -> Self::Result<'result> {
+        stream::once(ready(no_sqlx_result()))
+    }
+}
 
 impl QueryStrategyResult for Nothing {
     type Result<'result> = Ready<Result<(), Error>>;
@@ -97,10 +190,26 @@ impl QueryStrategyResult for Optional {
     type Result<'result> = Ready<Result<Option<Row>, Error>>;
 }
 
+impl QueryStrategyResult for ExactlyOne {
+    type Result<'result> = Ready<Result<Row, Error>>;
+}
+
+impl QueryStrategyResult for AtMostOne {
+    type Result<'result> = Ready<Result<Option<Row>, Error>>;
+}
+
 impl QueryStrategyResult for All {
     type Result<'result> = Ready<Result<Vec<Row>, Error>>;
 }
 
+impl QueryStrategyResult for AllWithAffected {
+    type Result<'result> = Ready<Result<(Vec<Row>, u64), Error>>;
+}
+
+impl QueryStrategyResult for MultiResultSet {
+    type Result<'result> = Ready<Result<Vec<Vec<Row>>, Error>>;
+}
+
 impl QueryStrategyResult for Stream {
-    type Result<'result> = Empty<Result<Row, Error>>;
+    type Result<'result> = Once<Ready<Result<Row, Error>>>;
 }
@@ -1,8 +1,11 @@
+pub(crate) mod connection;
 pub(crate) mod database;
 pub(crate) mod executor;
 pub(crate) mod row;
 pub(crate) mod transaction;
 
+use crate::Error;
+
 pub(crate) fn no_sqlx() -> ! {
     panic!(
         r#"
@@ -14,5 +17,18 @@ pub(crate) fn no_sqlx() -> ! {
     );
 }
 
+/// Result of every fallible operation
+///
+/// It is an error if the `dummy-returns-error` feature is enabled, otherwise it panics using [`no_sqlx`].
+pub(crate) fn no_sqlx_result<T>() -> Result<T, Error> {
+    if cfg!(feature = "dummy-returns-error") {
+        Err(Error::ConfigurationError(String::from(
+            "no runtime configured",
+        )))
+    } else {
+        no_sqlx();
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub(crate) enum NotInstantiable {}
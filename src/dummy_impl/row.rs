@@ -1,4 +1,4 @@
-use super::{no_sqlx, NotInstantiable};
+use super::{no_sqlx, no_sqlx_result, NotInstantiable};
 use crate::row::{Decode, RowIndex};
 use crate::{Error, Row};
 
@@ -10,5 +10,31 @@ where
     T: Decode<'r>,
     I: RowIndex,
 {
+    no_sqlx_result()
+}
+
+/// Implementation of [Row::get_numeric_str]
+pub(crate) fn get_numeric_str<I>(_row: &Row, _index: I) -> Result<String, Error>
+where
+    I: RowIndex,
+{
+    no_sqlx_result()
+}
+
+/// Implementation of [Row::get_raw]
+pub(crate) fn get_raw<I>(_row: &Row, _index: I) -> Result<&[u8], Error>
+where
+    I: RowIndex,
+{
+    no_sqlx_result()
+}
+
+/// Implementation of [Row::len]
+pub(crate) fn len(_row: &Row) -> usize {
+    no_sqlx();
+}
+
+/// Implementation of [Row::column_names]
+pub(crate) fn column_names(_row: &Row) -> Vec<&str> {
     no_sqlx();
 }
@@ -1,17 +1,44 @@
-use super::{no_sqlx, NotInstantiable};
+use rorm_sql::DBImpl;
+
+use super::{no_sqlx, no_sqlx_result, NotInstantiable};
 use crate::transaction::Transaction;
 use crate::Error;
 
 pub(crate) type Impl = NotInstantiable;
 
+/// Implementation of [Executor::dialect](crate::executor::Executor::dialect) for a shared transaction
+pub(crate) fn dialect(transaction: &Transaction) -> DBImpl {
+    match transaction.0 {}
+}
+
 /// Implementation of [Transaction::commit]
 pub(crate) async fn commit(transaction: Transaction) -> Result<(), Error> {
     // "Read" tx at least once
     let _ = transaction.0;
-    no_sqlx();
+    no_sqlx_result()
 }
 
 /// Implementation of [Transaction::rollback]
 pub(crate) async fn rollback(_transaction: Transaction) -> Result<(), Error> {
+    no_sqlx_result()
+}
+
+/// Implementation of [Transaction::start_transaction]
+pub(crate) async fn begin_savepoint(_transaction: &mut Transaction) -> Result<(), Error> {
+    no_sqlx_result()
+}
+
+/// Implementation of [Savepoint::commit](crate::transaction::Savepoint::commit)
+pub(crate) async fn commit_savepoint(_transaction: &mut Transaction) -> Result<(), Error> {
+    no_sqlx_result()
+}
+
+/// Implementation of [Savepoint::rollback](crate::transaction::Savepoint::rollback)
+pub(crate) async fn rollback_savepoint(_transaction: &mut Transaction) -> Result<(), Error> {
+    no_sqlx_result()
+}
+
+/// Implementation of [Savepoint::drop](crate::transaction::Savepoint)
+pub(crate) fn start_rollback_savepoint(_transaction: &mut Transaction) {
     no_sqlx();
 }
@@ -1,5 +1,6 @@
 //! Error type to simplify propagating different error types.
 
+use std::ops::Range;
 use std::{error, fmt};
 
 #[cfg(not(feature = "sqlx"))]
@@ -35,6 +36,76 @@ pub enum Error {
 
     /// SQL building error
     SQLBuildError(rorm_sql::error::Error),
+
+    /// A value couldn't be bound as a query parameter
+    BindError(String),
+
+    /// A query expected to return a row returned none
+    ///
+    /// See [`One`](crate::executor::One) and [`ExactlyOne`](crate::executor::ExactlyOne)
+    RowNotFound,
+
+    /// A query expected to return at most one row returned more
+    ///
+    /// See [`ExactlyOne`](crate::executor::ExactlyOne) and [`AtMostOne`](crate::executor::AtMostOne)
+    TooManyRows,
+
+    /// Reading or writing a file or stream failed
+    ///
+    /// For example, see [`Database::execute_file`](crate::Database::execute_file)
+    IoError(std::io::Error),
+
+    /// An operation didn't complete in its configured time
+    ///
+    /// See [`DatabaseConfiguration::begin_timeout`](crate::DatabaseConfiguration::begin_timeout)
+    Timeout,
+
+    /// A query requiring a row has been captured by a [`DryRunExecutor`](crate::executor::DryRunExecutor)
+    /// which never returns any rows
+    DryRun,
+
+    /// A chunk of a bulk insert failed
+    BulkInsertError {
+        /// Index of the failing chunk
+        chunk: usize,
+        /// Offsets of the rows in the failing chunk
+        rows: Range<usize>,
+        /// The error the chunk failed with
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Checks whether the statement has been aborted to resolve a deadlock
+    ///
+    /// Deadlocks occur in normal operation when transactions lock rows in different orders,
+    /// so the aborted transaction can usually just be retried.
+    ///
+    /// - Postgres reports `40P01` (`deadlock_detected`).
+    /// - MySQL reports error `1213` (`ER_LOCK_DEADLOCK`).
+    /// - SQLite doesn't detect deadlocks, instead a locked database is reported as busy.
+    #[cfg_attr(
+        not(any(feature = "postgres", feature = "mysql")),
+        allow(unused_variables)
+    )]
+    pub fn is_deadlock(&self) -> bool {
+        match self {
+            #[cfg(feature = "sqlx")]
+            Error::SqlxError(sqlx::Error::Database(error)) => {
+                #[cfg(feature = "postgres")]
+                if error.code().as_deref() == Some("40P01") {
+                    return true;
+                }
+                #[cfg(feature = "mysql")]
+                if let Some(error) = error.try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>() {
+                    return error.number() == 1213;
+                }
+                false
+            }
+            Error::BulkInsertError { source, .. } => source.is_deadlock(),
+            _ => false,
+        }
+    }
 }
 
 impl error::Error for Error {
@@ -44,6 +115,13 @@ impl error::Error for Error {
             Error::ConfigurationError(_) => None,
             Error::DecodeError(_) => None,
             Error::SQLBuildError(source) => Some(source),
+            Error::BindError(_) => None,
+            Error::RowNotFound => None,
+            Error::TooManyRows => None,
+            Error::IoError(source) => Some(source),
+            Error::Timeout => None,
+            Error::DryRun => None,
+            Error::BulkInsertError { source, .. } => Some(source.as_ref()),
         }
     }
 }
@@ -59,6 +137,21 @@ impl fmt::Display for Error {
             Error::SQLBuildError(error) => {
                 write!(f, "sql error: {error}")
             }
+            Error::BindError(error) => write!(f, "bind error: {error}"),
+            Error::RowNotFound => write!(f, "query returned no rows"),
+            Error::TooManyRows => write!(f, "query returned more than one row"),
+            Error::IoError(error) => write!(f, "io error: {error}"),
+            Error::Timeout => write!(f, "operation timed out"),
+            Error::DryRun => write!(f, "query was not executed in a dry run"),
+            Error::BulkInsertError {
+                chunk,
+                rows,
+                source,
+            } => write!(
+                f,
+                "bulk insert failed in chunk {chunk} at rows {}..{}: {source}",
+                rows.start, rows.end
+            ),
         }
     }
 }
@@ -69,6 +162,12 @@ impl From<SqlxError> for Error {
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(source: std::io::Error) -> Self {
+        Error::IoError(source)
+    }
+}
+
 impl From<rorm_sql::error::Error> for Error {
     fn from(source: rorm_sql::error::Error) -> Self {
         Error::SQLBuildError(source)
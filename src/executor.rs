@@ -4,11 +4,15 @@
 //! our [`Executor`] has a single method which is generic using the [`QueryStrategy`] trait.
 
 use std::future::Future;
+use std::ops::Range;
+use std::sync::{Mutex, MutexGuard};
 
 use futures::future::BoxFuture;
 use rorm_sql::value::Value;
 use rorm_sql::DBImpl;
 
+use crate::connection::Connection;
+use crate::row::{DecodeOwned, FromRow};
 use crate::transaction::{Transaction, TransactionGuard};
 use crate::{internal, Database, Error};
 
@@ -22,6 +26,9 @@ impl QueryStrategy for Nothing {}
 /// [`QueryStrategy`] returning how many rows have been affected by the query
 ///
 /// `type Result<'result> = impl Future<Output = Result<u64, Error>>`
+///
+/// Rows returned by the query (for example by a `SELECT`) are discarded and don't count as affected.
+/// Since this is most likely a mistake, it is logged on the debug level.
 pub struct AffectedRows;
 
 impl QueryStrategy for AffectedRows {}
@@ -29,6 +36,8 @@ impl QueryStrategy for AffectedRows {}
 /// [`QueryStrategy`] returning a single row
 ///
 /// `type Result<'result> = impl Future<Output = Result<Row, Error>>`
+///
+/// Fails with [`Error::RowNotFound`] if the query doesn't return any row.
 pub struct One;
 
 impl QueryStrategy for One {}
@@ -40,6 +49,28 @@ pub struct Optional;
 
 impl QueryStrategy for Optional {}
 
+/// [`QueryStrategy`] returning a single row and failing if there are more
+///
+/// `type Result<'result> = impl Future<Output = Result<Row, Error>>`
+///
+/// Unlike [`One`], which ignores any further rows,
+/// this fails with [`Error::TooManyRows`] if the query returns a second row.
+/// Like [`One`], it fails with [`Error::RowNotFound`] if the query doesn't return any row.
+/// Use it for queries which are expected to match a single row, e.g. by a unique key.
+pub struct ExactlyOne;
+
+impl QueryStrategy for ExactlyOne {}
+
+/// [`QueryStrategy`] returning an optional row and failing if there are more
+///
+/// `type Result<'result> = impl Future<Output = Result<Option<Row>, Error>>`
+///
+/// Unlike [`Optional`], which ignores any further rows,
+/// this fails with [`Error::TooManyRows`] if the query returns a second row.
+pub struct AtMostOne;
+
+impl QueryStrategy for AtMostOne {}
+
 /// [`QueryStrategy`] returning a vector of rows
 ///
 /// `type Result<'result> = impl Future<Output = Result<Vec<Row>, Error>>`
@@ -47,9 +78,42 @@ pub struct All;
 
 impl QueryStrategy for All {}
 
+/// [`QueryStrategy`] returning all rows together with the number of affected rows
+///
+/// `type Result<'result> = impl Future<Output = Result<(Vec<Row>, u64), Error>>`
+///
+/// This is useful for statements which both change and return rows,
+/// like `UPDATE ... RETURNING *` on Postgres and SQLite.
+/// The affected rows of all result sets are summed up.
+pub struct AllWithAffected;
+
+impl QueryStrategy for AllWithAffected {}
+
+/// [`QueryStrategy`] returning the rows of every result set separately
+///
+/// `type Result<'result> = impl Future<Output = Result<Vec<Vec<Row>>, Error>>`
+///
+/// Some queries produce several result sets,
+/// for example MySQL's stored procedures or several statements executed at once.
+/// Every statement ends its result set, even if it doesn't return any rows (e.g. an `UPDATE`),
+/// so such statements produce empty result sets.
+/// Note that MySQL's `CALL` produces an additional empty result set for the call itself.
+pub struct MultiResultSet;
+
+impl QueryStrategy for MultiResultSet {}
+
 /// [`QueryStrategy`] returning a stream of rows
 ///
 /// `type Result<'result> = impl Stream<Item = Result<Row, Error>>`
+///
+/// Rows are decoded one at a time as the stream is polled, but none of the drivers use
+/// server side cursors, so there is no fetch size to tune:
+/// - Postgres and MySQL send the whole result set and the rows are read from the connection's socket.
+///   A consumer which stops polling applies backpressure to the server once the socket's buffers are full.
+/// - SQLite steps its statement once per polled row.
+///
+/// To fetch a Postgres result in bounded batches,
+/// use `Transaction::declare_cursor` and `Transaction::fetch_cursor` (requires the `postgres` feature).
 pub struct Stream;
 
 impl QueryStrategy for Stream {}
@@ -60,8 +124,12 @@ impl QueryStrategy for Stream {}
 /// - [`Nothing`] retrieves nothing
 /// - [`Optional`] retrieves an optional row
 /// - [`One`] retrieves a single row
+/// - [`AtMostOne`] retrieves an optional row and fails on a second one
+/// - [`ExactlyOne`] retrieves a single row and fails on a second one
 /// - [`Stream`] retrieves many rows in a stream
 /// - [`All`] retrieves many rows in a vector
+/// - [`AllWithAffected`] retrieves many rows in a vector and the number of rows affected
+/// - [`MultiResultSet`] retrieves many rows grouped by their result sets
 /// - [`AffectedRows`] returns the number of rows affected by the query
 ///
 /// This trait has an associated `Result<'result>` type which is returned by [`Executor::execute`].
@@ -95,6 +163,10 @@ pub trait Executor<'executor> {
     /// The generic `Q` is used to "select" what the database is supposed to respond with.
     /// See [`QueryStrategy`] for a list of available options.
     ///
+    /// [`Value`]s only borrow large payloads like strings, binary data or json,
+    /// so building `values` doesn't copy them.
+    /// Each value is encoded once directly into the driver's buffer.
+    ///
     /// ```skipped
     /// db.execute::<All>("SELECT * FROM foo;".to_string(), vec![]);
     /// ```
@@ -108,9 +180,92 @@ pub trait Executor<'executor> {
         'data: 'result,
         Q: QueryStrategy;
 
+    /// Executes a raw SQL query and decodes the first column of every returned row
+    ///
+    /// ```skipped
+    /// let ids: Vec<i64> = db.fetch_scalars("SELECT id FROM foo;".to_string(), vec![]).await?;
+    /// ```
+    fn fetch_scalars<'data, 'result, T>(
+        self,
+        query: String,
+        values: Vec<Value<'data>>,
+    ) -> BoxFuture<'result, Result<Vec<T>, Error>>
+    where
+        Self: Sized,
+        'executor: 'result,
+        'data: 'result,
+        T: DecodeOwned + Send + 'result,
+    {
+        let rows = self.execute::<All>(query, values);
+        Box::pin(async move { rows.await?.iter().map(|row| row.get(0)).collect() })
+    }
+
+    /// Executes a raw SQL query returning a single row and decodes it using [`FromRow`]
+    ///
+    /// See [`One`] for how the rows are retrieved.
+    fn fetch_one_as<'data, 'result, T>(
+        self,
+        query: String,
+        values: Vec<Value<'data>>,
+    ) -> BoxFuture<'result, Result<T, Error>>
+    where
+        Self: Sized,
+        'executor: 'result,
+        'data: 'result,
+        T: FromRow + Send + 'result,
+    {
+        let row = self.execute::<One>(query, values);
+        Box::pin(async move { T::from_row(row.await?) })
+    }
+
+    /// Executes a raw SQL query returning an optional row and decodes it using [`FromRow`]
+    ///
+    /// See [`Optional`] for how the rows are retrieved.
+    fn fetch_optional_as<'data, 'result, T>(
+        self,
+        query: String,
+        values: Vec<Value<'data>>,
+    ) -> BoxFuture<'result, Result<Option<T>, Error>>
+    where
+        Self: Sized,
+        'executor: 'result,
+        'data: 'result,
+        T: FromRow + Send + 'result,
+    {
+        let row = self.execute::<Optional>(query, values);
+        Box::pin(async move { row.await?.map(T::from_row).transpose() })
+    }
+
+    /// Executes a raw SQL query and decodes every returned row using [`FromRow`]
+    ///
+    /// ```skipped
+    /// let users: Vec<User> = db.fetch_all_as("SELECT * FROM user;".to_string(), vec![]).await?;
+    /// ```
+    fn fetch_all_as<'data, 'result, T>(
+        self,
+        query: String,
+        values: Vec<Value<'data>>,
+    ) -> BoxFuture<'result, Result<Vec<T>, Error>>
+    where
+        Self: Sized,
+        'executor: 'result,
+        'data: 'result,
+        T: FromRow + Send + 'result,
+    {
+        let rows = self.execute::<All>(query, values);
+        Box::pin(async move { rows.await?.into_iter().map(T::from_row).collect() })
+    }
+
     /// Get the executor's sql dialect.
     fn dialect(&self) -> DBImpl;
 
+    /// Quote an identifier like a table or column name in the executor's sql dialect.
+    ///
+    /// See [`quote_identifier`]
+    fn quote_identifier(&self, identifier: &str) -> String {
+        quote_identifier(self.dialect(), identifier)
+    }
+
     /// Convenience method to convert into a "`dyn Executor`"
     fn into_dyn(self) -> DynamicExecutor<'executor>;
 
@@ -129,6 +284,168 @@ pub trait Executor<'executor> {
         -> BoxFuture<'executor, Result<TransactionGuard<'executor>, Error>>;
 }
 
+/// Quote an identifier like a table or column name in a sql dialect.
+///
+/// Postgres and SQLite use double quotes (`"order"`), MySQL uses backticks (`` `order` ``).
+/// Quote characters inside the identifier are escaped by doubling them.
+///
+/// Use this when building queries with dynamic names which might collide with reserved words.
+pub fn quote_identifier(dialect: DBImpl, identifier: &str) -> String {
+    match dialect {
+        #[cfg(feature = "mysql")]
+        DBImpl::MySQL => format!("`{}`", identifier.replace('`', "``")),
+        #[cfg(feature = "postgres")]
+        DBImpl::Postgres => format!("\"{}\"", identifier.replace('"', "\"\"")),
+        #[cfg(feature = "sqlite")]
+        DBImpl::SQLite => format!("\"{}\"", identifier.replace('"', "\"\"")),
+    }
+}
+
+/// Rewrite a query's placeholders into the style used by a sql dialect.
+///
+/// This allows writing a single query string for all dialects:
+/// - Postgres: `?` are numbered in order of appearance i.e. `$1`, `$2`, ...
+/// - SQLite: `$n` are turned into `?n` which keeps their numbering
+/// - MySQL: `$n` are turned into `?`.
+///   Since MySQL binds positionally, they have to appear in ascending order and only once.
+///
+/// Placeholder-like characters inside string literals, quoted identifiers,
+/// comments and postgres' dollar quoted strings are left untouched.
+///
+/// **Beware**, postgres' jsonb operators `?`, `?|` and `?&` are indistinguishable from placeholders.
+pub fn rebind_placeholders(sql: &str, dialect: DBImpl) -> String {
+    let mut output = String::with_capacity(sql.len());
+    // Start of the slice which has been scanned but not yet copied to `output`
+    let mut copied = 0;
+    #[cfg_attr(not(feature = "postgres"), allow(unused_mut, unused_variables))]
+    let mut counter = 0;
+
+    for_each_placeholder(sql, is_mysql(dialect), |range, placeholder| {
+        let replacement = match (dialect, placeholder) {
+            #[cfg(feature = "postgres")]
+            (DBImpl::Postgres, Placeholder::Question) => {
+                counter += 1;
+                format!("${counter}")
+            }
+            #[cfg(feature = "mysql")]
+            (DBImpl::MySQL, Placeholder::Dollar(_)) => String::from("?"),
+            #[cfg(feature = "sqlite")]
+            (DBImpl::SQLite, Placeholder::Dollar(number)) => format!("?{number}"),
+            _ => return,
+        };
+        output.push_str(&sql[copied..range.start]);
+        output.push_str(&replacement);
+        copied = range.end;
+    });
+
+    output.push_str(&sql[copied..]);
+    output
+}
+
+/// Count the parameters a query expects based on its placeholders
+///
+/// This is the number of `?` or the highest `$n`, whichever is larger.
+/// Like [`rebind_placeholders`], it can't distinguish postgres' jsonb operators from placeholders.
+#[cfg(any(test, all(feature = "sqlx", debug_assertions)))]
+pub(crate) fn count_placeholders(sql: &str) -> usize {
+    let mut questions = 0;
+    let mut highest = 0;
+    for_each_placeholder(sql, false, |_, placeholder| match placeholder {
+        Placeholder::Question => questions += 1,
+        Placeholder::Dollar(number) => highest = highest.max(number),
+    });
+    questions.max(highest)
+}
+
+/// Placeholder found by [`for_each_placeholder`]
+enum Placeholder {
+    /// `?`
+    Question,
+    /// `$n`
+    Dollar(usize),
+}
+
+/// Call `f` with the byte range of every placeholder in `sql`
+///
+/// Skips string literals, quoted identifiers, comments and postgres' dollar quoted strings.
+/// `backslash_escapes` enables MySQL's escaping of quotes inside string literals using backslashes.
+fn for_each_placeholder(
+    sql: &str,
+    backslash_escapes: bool,
+    mut f: impl FnMut(Range<usize>, Placeholder),
+) {
+    let bytes = sql.as_bytes();
+    let mut index = 0;
+
+    // Find the index after the first occurrence of `pattern` at or after `start`
+    let skip_past = |start: usize, pattern: &str| {
+        sql[start..]
+            .find(pattern)
+            .map_or(sql.len(), |offset| start + offset + pattern.len())
+    };
+
+    while index < bytes.len() {
+        match bytes[index] {
+            b'\'' => {
+                index += 1;
+                while index < bytes.len() && bytes[index] != b'\'' {
+                    if bytes[index] == b'\\' && backslash_escapes {
+                        index += 1;
+                    }
+                    index += 1;
+                }
+                index += 1;
+            }
+            // Doubled quotes are handled as two adjacent quoted sections
+            b'"' => index = skip_past(index + 1, "\""),
+            b'`' => index = skip_past(index + 1, "`"),
+            b'-' if bytes.get(index + 1) == Some(&b'-') => index = skip_past(index + 2, "\n"),
+            b'/' if bytes.get(index + 1) == Some(&b'*') => index = skip_past(index + 2, "*/"),
+            b'?' => {
+                f(index..index + 1, Placeholder::Question);
+                index += 1;
+            }
+            b'$' => {
+                let digits = bytes[index + 1..]
+                    .iter()
+                    .take_while(|byte| byte.is_ascii_digit())
+                    .count();
+                if digits > 0 {
+                    let end = index + 1 + digits;
+                    // Too many digits to parse can't be a meaningful placeholder anyway
+                    if let Ok(number) = sql[index + 1..end].parse() {
+                        f(index..end, Placeholder::Dollar(number));
+                    }
+                    index = end;
+                } else {
+                    // Dollar quoted string i.e. `$tag$...$tag$` or `$$...$$`
+                    let tag_length = bytes[index + 1..]
+                        .iter()
+                        .take_while(|byte| byte.is_ascii_alphanumeric() || **byte == b'_')
+                        .count();
+                    let tag_end = index + 1 + tag_length;
+                    if bytes.get(tag_end) == Some(&b'$') {
+                        index = skip_past(tag_end + 1, &sql[index..=tag_end]);
+                    } else {
+                        index += 1;
+                    }
+                }
+            }
+            _ => index += 1,
+        }
+    }
+}
+
+/// Helper for [`rebind_placeholders`] which compiles without the mysql feature
+fn is_mysql(dialect: DBImpl) -> bool {
+    match dialect {
+        #[cfg(feature = "mysql")]
+        DBImpl::MySQL => true,
+        #[allow(unreachable_patterns)]
+        _ => false,
+    }
+}
+
 /// Choose whether to use transactions or not at runtime
 ///
 /// Like a `Box<dyn Executor<'executor>>`
@@ -137,6 +454,73 @@ pub enum DynamicExecutor<'executor> {
     Database(&'executor Database),
     /// Use a transaction
     Transaction(&'executor mut Transaction),
+    /// Use a single pinned connection
+    Connection(&'executor mut Connection),
+    /// Capture the queries without executing them
+    DryRun(&'executor DryRunExecutor),
+}
+
+/// [`Executor`] which captures the queries instead of executing them
+///
+/// This can be used to preview the sql and the bound values
+/// produced by the functions in the [`database`](crate::database) module:
+///
+/// ```skipped
+/// let dry_run = DryRunExecutor::new(DBImpl::Postgres);
+/// let _ = database::insert(&dry_run, "user", &["name"], &[Value::String("alice")]).await;
+/// let statements = dry_run.take_statements();
+/// ```
+///
+/// Every query behaves as if it didn't return any rows or affect anything
+/// e.g. [`All`] produces an empty vector and [`AffectedRows`] produces `0`.
+/// Only the strategies requiring a row ([`One`] and [`ExactlyOne`]) fail with an [`Error::DryRun`],
+/// so functions depending on such a query's result stop capturing afterwards.
+/// [`Executor::ensure_transaction`] succeeds with a [`TransactionGuard::DryRun`],
+/// so functions which require a transaction (e.g. [`insert_bulk`](crate::database::insert_bulk))
+/// capture their statements as well.
+pub struct DryRunExecutor {
+    pub(crate) dialect: DBImpl,
+    statements: Mutex<Vec<DryRunStatement>>,
+}
+
+/// Query captured by a [`DryRunExecutor`]
+#[derive(Clone, Debug)]
+pub struct DryRunStatement {
+    /// The query's sql
+    pub query: String,
+
+    /// The `Debug` representations of the values bound to the query's placeholders
+    pub values: Vec<String>,
+}
+
+impl DryRunExecutor {
+    /// Creates an executor producing queries for the sql `dialect`
+    pub fn new(dialect: DBImpl) -> Self {
+        Self {
+            dialect,
+            statements: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Takes the queries captured so far in the order they were executed
+    pub fn take_statements(&self) -> Vec<DryRunStatement> {
+        std::mem::take(&mut *self.lock_statements())
+    }
+
+    /// Captures a query passed to [`Executor::execute`]
+    pub(crate) fn capture(&self, query: &str, values: &[Value<'_>]) {
+        self.lock_statements().push(DryRunStatement {
+            query: query.to_string(),
+            values: values.iter().map(|value| format!("{value:?}")).collect(),
+        });
+    }
+
+    fn lock_statements(&self) -> MutexGuard<'_, Vec<DryRunStatement>> {
+        // The captured statements stay consistent even if a thread panicked while holding the lock
+        self.statements
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
 }
 
 impl<'executor> Executor<'executor> for DynamicExecutor<'executor> {
@@ -153,6 +537,8 @@ impl<'executor> Executor<'executor> for DynamicExecutor<'executor> {
         match self {
             DynamicExecutor::Database(db) => db.execute::<Q>(query, values),
             DynamicExecutor::Transaction(tr) => tr.execute::<Q>(query, values),
+            DynamicExecutor::Connection(conn) => conn.execute::<Q>(query, values),
+            DynamicExecutor::DryRun(dry_run) => dry_run.execute::<Q>(query, values),
         }
     }
 
@@ -160,6 +546,8 @@ impl<'executor> Executor<'executor> for DynamicExecutor<'executor> {
         match self {
             DynamicExecutor::Database(db) => db.dialect(),
             DynamicExecutor::Transaction(tr) => tr.dialect(),
+            DynamicExecutor::Connection(conn) => conn.dialect(),
+            DynamicExecutor::DryRun(dry_run) => dry_run.dialect(),
         }
     }
 
@@ -173,6 +561,64 @@ impl<'executor> Executor<'executor> for DynamicExecutor<'executor> {
         match self {
             DynamicExecutor::Database(db) => db.ensure_transaction(),
             DynamicExecutor::Transaction(tr) => Box::pin(tr.ensure_transaction()),
+            DynamicExecutor::Connection(conn) => Box::pin(conn.ensure_transaction()),
+            DynamicExecutor::DryRun(dry_run) => dry_run.ensure_transaction(),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use rorm_sql::DBImpl;
+
+    use super::{count_placeholders, rebind_placeholders};
+
+    #[test]
+    #[cfg(feature = "postgres")]
+    fn test_rebind_postgres() {
+        assert_eq!(
+            rebind_placeholders(
+                "SELECT '?', \"a?\" FROM t WHERE a = ? -- ?\n AND b = ? /* ? */ AND c = $$?$$;",
+                DBImpl::Postgres
+            ),
+            "SELECT '?', \"a?\" FROM t WHERE a = $1 -- ?\n AND b = $2 /* ? */ AND c = $$?$$;"
+        );
+    }
+
+    #[test]
+    fn test_count_placeholders() {
+        assert_eq!(
+            count_placeholders("SELECT * FROM t WHERE a = ? AND b = '?';"),
+            1
+        );
+        assert_eq!(
+            count_placeholders("SELECT * FROM t WHERE a = $2 AND b = $1;"),
+            2
+        );
+        assert_eq!(count_placeholders("SELECT $$?$$ -- ?\n;"), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "mysql")]
+    fn test_rebind_mysql() {
+        assert_eq!(
+            rebind_placeholders(
+                "SELECT 'it\\'s $1' FROM t WHERE a = $1 AND b = $2;",
+                DBImpl::MySQL
+            ),
+            "SELECT 'it\\'s $1' FROM t WHERE a = ? AND b = ?;"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sqlite")]
+    fn test_rebind_sqlite() {
+        assert_eq!(
+            rebind_placeholders(
+                "SELECT * FROM t WHERE a = $2 AND b = $1 AND c = ?;",
+                DBImpl::SQLite
+            ),
+            "SELECT * FROM t WHERE a = ?2 AND b = ?1 AND c = ?;"
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! Postgres' `hstore` extension type
+
+use std::collections::HashMap;
+
+/// Map from strings to optional strings stored in postgres' `hstore` extension type
+///
+/// Read it using [`Row::get`](crate::Row::get).
+/// It can't be bound yet, because rorm-sql's `Value` has no variant for it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Hstore(pub HashMap<String, Option<String>>);
+
+const _: () = {
+    use sqlx::error::BoxDynError;
+    use sqlx::postgres::{PgTypeInfo, PgValueFormat, PgValueRef};
+    use sqlx::{Decode, Postgres, Type};
+
+    impl Type<Postgres> for Hstore {
+        fn type_info() -> PgTypeInfo {
+            // Since `hstore` is enabled by an extension, it does not have a stable OID.
+            PgTypeInfo::with_name("hstore")
+        }
+    }
+
+    impl<'r> Decode<'r, Postgres> for Hstore {
+        fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+            match value.format() {
+                PgValueFormat::Binary => decode_binary(value.as_bytes()?).map(Self),
+                PgValueFormat::Text => decode_text(value.as_str()?).map(Self),
+            }
+        }
+    }
+
+    /// Decode the format produced by postgres' `hstore_send`
+    fn decode_binary(mut buf: &[u8]) -> Result<HashMap<String, Option<String>>, BoxDynError> {
+        fn read_i32(buf: &mut &[u8]) -> Result<i32, BoxDynError> {
+            if buf.len() < 4 {
+                return Err("unexpected end of hstore".into());
+            }
+            let (head, tail) = buf.split_at(4);
+            *buf = tail;
+            Ok(i32::from_be_bytes(head.try_into()?))
+        }
+        fn read_string(buf: &mut &[u8], len: i32) -> Result<String, BoxDynError> {
+            let len = usize::try_from(len)?;
+            if buf.len() < len {
+                return Err("unexpected end of hstore".into());
+            }
+            let (head, tail) = buf.split_at(len);
+            *buf = tail;
+            Ok(String::from_utf8(head.to_vec())?)
+        }
+
+        let count = read_i32(&mut buf)?;
+        let mut map = HashMap::with_capacity(usize::try_from(count)?);
+        for _ in 0..count {
+            let key_len = read_i32(&mut buf)?;
+            let key = read_string(&mut buf, key_len)?;
+            let value_len = read_i32(&mut buf)?;
+            let value = if value_len < 0 {
+                None
+            } else {
+                Some(read_string(&mut buf, value_len)?)
+            };
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+};
+
+/// Decode the format produced by postgres' `hstore_out` i.e. `"key"=>"value", "other"=>NULL`
+fn decode_text(
+    mut input: &str,
+) -> Result<HashMap<String, Option<String>>, Box<dyn std::error::Error + Send + Sync>> {
+    fn parse_quoted(input: &str) -> Result<(String, &str), &'static str> {
+        let input = input.strip_prefix('"').ok_or("expected '\"' in hstore")?;
+        let mut string = String::new();
+        let mut chars = input.char_indices();
+        while let Some((index, char)) = chars.next() {
+            match char {
+                '"' => return Ok((string, &input[index + 1..])),
+                '\\' => string.push(chars.next().ok_or("unexpected end of hstore")?.1),
+                char => string.push(char),
+            }
+        }
+        Err("unterminated string in hstore")
+    }
+
+    let mut map = HashMap::new();
+    loop {
+        input = input.trim_start();
+        if input.is_empty() {
+            return Ok(map);
+        }
+
+        let (key, rest) = parse_quoted(input)?;
+        let rest = rest
+            .trim_start()
+            .strip_prefix("=>")
+            .ok_or("expected '=>' in hstore")?
+            .trim_start();
+        let (value, rest) = match rest.strip_prefix("NULL") {
+            Some(rest) => (None, rest),
+            None => {
+                let (value, rest) = parse_quoted(rest)?;
+                (Some(value), rest)
+            }
+        };
+        map.insert(key, value);
+
+        input = rest.trim_start();
+        if !input.is_empty() {
+            input = input.strip_prefix(',').ok_or("expected ',' in hstore")?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::decode_text;
+
+    #[test]
+    fn test_decode_text() {
+        let map = decode_text(r#""a"=>"1", "b \"quoted\""=>NULL, "c"=>"back\\slash""#).unwrap();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["a"].as_deref(), Some("1"));
+        assert_eq!(map["b \"quoted\""], None);
+        assert_eq!(map["c"].as_deref(), Some("back\\slash"));
+
+        assert!(decode_text("").unwrap().is_empty());
+        assert!(decode_text(r#""a"=>"1" "b"=>"2""#).is_err());
+    }
+}
@@ -25,15 +25,27 @@
 ))]
 compile_error!("Using multiple runtime / tls configurations at the same time is not allowed");
 
+#[cfg(all(feature = "postgres-only", any(feature = "mysql", feature = "sqlite")))]
+compile_error!(
+    "The feature postgres-only can't be combined with mysql or sqlite: \
+    the postgres specific types (MacAddress, IpNetwork, BitVec) can't be bound to other databases"
+);
+
 pub mod database;
 pub mod error;
 
 pub(crate) mod query_type;
 
 pub mod choice;
+pub mod connection;
 pub mod executor;
+#[cfg(feature = "postgres-only")]
+pub mod hstore;
+pub mod money;
 pub mod row;
 pub mod transaction;
+#[cfg(feature = "postgres-only")]
+pub mod wkb;
 
 #[cfg_attr(feature = "sqlx", path = "sqlx_impl/mod.rs")]
 #[cfg_attr(not(feature = "sqlx"), path = "dummy_impl/mod.rs")]
@@ -46,7 +58,10 @@ pub mod sql {
 
 pub use rorm_declaration::config::DatabaseDriver;
 
-pub use crate::database::{Database, DatabaseConfiguration};
+pub use crate::connection::Connection;
+pub use crate::database::{
+    Database, DatabaseConfiguration, IsolationLevel, SslMode, StatementLogFormat,
+};
 pub use crate::error::Error;
 pub use crate::executor::Executor;
-pub use crate::row::Row;
+pub use crate::row::{FromRow, Row};
@@ -0,0 +1,20 @@
+//! Helpers to store amounts of money as integer cents
+//!
+//! Storing money in a `BIGINT` column as the number of the currency's smallest unit (e.g. cents)
+//! avoids the rounding errors of floating point numbers.
+//! These helpers don't add any conversion, they only make the unit explicit at the call site:
+//!
+//! ```skipped
+//! let values = [money::cents(1999)];
+//! // ...
+//! let price = row.get_money("price")?;
+//! ```
+//!
+//! Use [`Row::get_money`](crate::Row::get_money) to read an amount.
+
+use rorm_sql::value::Value;
+
+/// Creates the [`Value`] binding an amount of money given in cents
+pub fn cents(cents: i64) -> Value<'static> {
+    Value::I64(cents)
+}
@@ -1,6 +1,6 @@
 use rorm_sql::limit_clause::LimitClause;
 
-use crate::executor::{All, One, Optional, Stream};
+use crate::executor::{All, AtMostOne, ExactlyOne, One, Optional, Stream};
 
 type Offset = u64;
 
@@ -41,3 +41,21 @@ impl GetLimitClause for One {
         Some(LimitClause { limit: 1, offset })
     }
 }
+
+impl GetLimitClause for AtMostOne {
+    type LimitOrOffset = Offset;
+
+    fn get_limit_clause(offset: Option<Self::LimitOrOffset>) -> Option<LimitClause> {
+        // The second row is required to detect the error case
+        Some(LimitClause { limit: 2, offset })
+    }
+}
+
+impl GetLimitClause for ExactlyOne {
+    type LimitOrOffset = Offset;
+
+    fn get_limit_clause(offset: Option<Self::LimitOrOffset>) -> Option<LimitClause> {
+        // The second row is required to detect the error case
+        Some(LimitClause { limit: 2, offset })
+    }
+}
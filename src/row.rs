@@ -1,5 +1,12 @@
 //! This module defines a wrapper for sqlx's AnyRow
 
+#[cfg(feature = "sqlx")]
+use std::collections::HashMap;
+
+#[cfg(feature = "sqlx")]
+use sqlx::types::{chrono, JsonValue, Uuid};
+
+use crate::choice::{Choice, ChoiceEnum};
 use crate::error::Error;
 use crate::internal;
 
@@ -11,6 +18,15 @@ impl Row {
     ///
     /// A string index can be used to access a column by name
     /// and a `usize` index can be used to access a column by position.
+    ///
+    /// If the value can't be decoded, an [`Error::DecodeError`] naming the column and `T` is returned.
+    ///
+    /// # Strings
+    /// - `String` always allocates a copy of the cell.
+    /// - `&str` borrows the cell, so it can't outlive the row.
+    /// - `Cow<str>` borrows the cell as well, but can be turned into a `String` using
+    ///   [`Cow::into_owned`](std::borrow::Cow::into_owned) once it is clear that it has to be kept.
+    ///   This allows to decide lazily whether to pay for the allocation.
     pub fn get<'r, T, I>(&'r self, index: I) -> Result<T, Error>
     where
         T: Decode<'r>,
@@ -18,6 +34,351 @@ impl Row {
     {
         internal::row::get(self, index)
     }
+
+    /// Index into the database row and decode a single nullable value.
+    ///
+    /// Returns `None` if the value is `NULL`, otherwise behaves like [`Row::get`].
+    pub fn get_optional<'r, T, I>(&'r self, index: I) -> Result<Option<T>, Error>
+    where
+        Option<T>: Decode<'r>,
+        I: RowIndex,
+    {
+        self.get(index)
+    }
+
+    /// Index into the database row and decode a single value, using `default` if it is `NULL`.
+    ///
+    /// Errors other than the value being `NULL` are still returned.
+    pub fn get_or<T, I>(&self, index: I, default: T) -> Result<T, Error>
+    where
+        Option<T>: DecodeOwned,
+        I: RowIndex,
+    {
+        Ok(self.get_optional(index)?.unwrap_or(default))
+    }
+
+    /// Index into the database row and decode an amount of money stored as integer cents.
+    ///
+    /// This is the same as `get::<i64, _>` but makes the unit explicit,
+    /// see [`money`](crate::money).
+    pub fn get_money<I>(&self, index: I) -> Result<i64, Error>
+    where
+        I: RowIndex,
+    {
+        self.get(index)
+    }
+
+    /// Index into the database row and decode a [`Choice`](crate::choice::Choice) into a rust enum.
+    ///
+    /// This is the same as `get::<Choice, _>` followed by [`ChoiceEnum::from_choice`].
+    pub fn get_choice<E, I>(&self, index: I) -> Result<E, Error>
+    where
+        E: ChoiceEnum,
+        I: RowIndex,
+    {
+        E::from_choice(&self.get::<Choice, I>(index)?)
+    }
+
+    /// Index into the database row and get a cell's raw bytes without decoding them.
+    ///
+    /// The returned slice borrows from the row's internal buffer,
+    /// so the row has to outlive any use of it.
+    /// Copy the bytes to keep them around after the row is dropped.
+    ///
+    /// Unlike [`Row::get`], the column's type is not checked.
+    /// Postgres only supports this for values received in binary format i.e. from prepared statements.
+    pub fn get_raw<I>(&self, index: I) -> Result<&[u8], Error>
+    where
+        I: RowIndex,
+    {
+        internal::row::get_raw(self, index)
+    }
+
+    /// Index into the database row and get a `NUMERIC` / `DECIMAL` value in its exact textual form.
+    ///
+    /// This avoids the precision loss of decoding it as `f64`
+    /// when the value is only displayed or passed on.
+    ///
+    /// Postgres' binary format is converted into the same string Postgres would print.
+    /// Other databases return the value's text as sent by the database.
+    pub fn get_numeric_str<I>(&self, index: I) -> Result<String, Error>
+    where
+        I: RowIndex,
+    {
+        internal::row::get_numeric_str(self, index)
+    }
+
+    /// Index into the database row and get a 128 bit integer stored as `NUMERIC` / `DECIMAL` or text.
+    ///
+    /// None of the drivers decode 128 bit integers natively,
+    /// so the value's textual form (see [`Row::get_numeric_str`]) is parsed.
+    /// Values with a fractional part or outside of `i128`'s range return an [`Error::DecodeError`]
+    /// instead of being rounded or truncated.
+    ///
+    /// There is no [`Value`](rorm_sql::value::Value) for 128 bit integers,
+    /// bind them as `Value::String(&id.to_string())` instead.
+    /// Postgres requires a cast for that (e.g. `$1::NUMERIC`) and SQLite should store them as `TEXT`,
+    /// because its `INTEGER` and `REAL` can't hold them without loss.
+    pub fn get_i128<I>(&self, index: I) -> Result<i128, Error>
+    where
+        I: RowIndex,
+    {
+        self.get_numeric_str(index)
+            .and_then(|string| parse_integer(&string))
+    }
+
+    /// Index into the database row and get an unsigned 128 bit integer.
+    ///
+    /// See [`Row::get_i128`] for details.
+    pub fn get_u128<I>(&self, index: I) -> Result<u128, Error>
+    where
+        I: RowIndex,
+    {
+        self.get_numeric_str(index)
+            .and_then(|string| parse_integer(&string))
+    }
+
+    /// Decode the columns `start..end` which all have the same type into a `Vec`.
+    ///
+    /// If the range exceeds the row's columns, an [`Error::DecodeError`] is returned.
+    pub fn get_range<T>(&self, start: usize, end: usize) -> Result<Vec<T>, Error>
+    where
+        T: DecodeOwned,
+    {
+        let len = self.len();
+        if start > end || end > len {
+            return Err(Error::DecodeError(format!(
+                "the columns {start}..{end} are out of bounds for a row with {len} columns"
+            )));
+        }
+        (start..end).map(|index| self.get(index)).collect()
+    }
+
+    /// Get the number of the row's columns.
+    pub fn len(&self) -> usize {
+        internal::row::len(self)
+    }
+
+    /// Check whether the row has no columns.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the names of the row's columns in order.
+    pub fn column_names(&self) -> Vec<&str> {
+        internal::row::column_names(self)
+    }
+
+    /// Decode every cell into an [`OwnedValue`] chosen by its type and key it by its column's name.
+    ///
+    /// Types without a matching [`OwnedValue`] variant are decoded as text.
+    /// If several columns share a name, the last one wins.
+    #[cfg(feature = "sqlx")]
+    pub fn into_map(self) -> Result<HashMap<String, OwnedValue>, Error> {
+        internal::row::into_map(self)
+    }
+
+    /// Decode every cell into an [`OwnedValue`] chosen by its type while preserving the column order.
+    ///
+    /// Types without a matching [`OwnedValue`] variant are decoded as text.
+    #[cfg(feature = "sqlx")]
+    pub fn into_values(self) -> Result<Vec<OwnedValue>, Error> {
+        internal::row::into_values(self)
+    }
+
+    /// Index into the database row and get the cell's value in a textual form, whatever its type.
+    ///
+    /// This is an escape hatch for generic tools displaying values
+    /// whose types can't be decoded using [`Row::get`] with the enabled drivers.
+    ///
+    /// Values with a matching [`OwnedValue`] variant use their `Display` implementation
+    /// (`NUMERIC` / `DECIMAL` like [`Row::get_numeric_str`])
+    /// and binary data is printed as hex prefixed by `\x` like Postgres does.
+    /// Any other type is decoded as text which fails for types using a binary encoding.
+    /// `NULL` returns an [`Error::DecodeError`].
+    #[cfg(feature = "sqlx")]
+    pub fn get_as_string(&self, index: usize) -> Result<String, Error> {
+        internal::row::get_as_string(self, index)
+    }
+
+    /// Render the row's column names and values in a human readable form.
+    ///
+    /// Meant for tests and debugging i.e. to produce readable assertion failures:
+    /// `{id: I64(1), name: String("foo")}`
+    ///
+    /// Cells which can't be decoded are rendered as the error which occurred.
+    #[cfg(feature = "sqlx")]
+    pub fn debug_dump(&self) -> String {
+        let cells: Vec<_> = self
+            .column_names()
+            .into_iter()
+            .enumerate()
+            .map(
+                |(index, name)| match internal::row::get_owned(self, index) {
+                    Ok(value) => format!("{name}: {value:?}"),
+                    Err(error) => format!("{name}: <{error}>"),
+                },
+            )
+            .collect();
+        format!("{{{}}}", cells.join(", "))
+    }
+}
+
+/// Parse the textual form of an integer for [`Row::get_i128`] and [`Row::get_u128`]
+fn parse_integer<T: std::str::FromStr>(string: &str) -> Result<T, Error>
+where
+    T::Err: std::fmt::Display,
+{
+    string.parse().map_err(|error| {
+        Error::DecodeError(format!(
+            "could not decode {string:?} as {}: {error}",
+            std::any::type_name::<T>()
+        ))
+    })
+}
+
+/// Something which can be decoded from a whole [`Row`]
+///
+/// # Positional vs by name
+/// A row's cells can be looked up by their position or by their column's name:
+/// - Positional decoding (used by the tuple implementations and [`from_row_by_position!`](crate::from_row_by_position))
+///   is slightly cheaper but silently breaks
+///   when the query's columns are reordered or new ones are inserted.
+///   It fits queries which are built right next to the decoding.
+/// - Decoding by name (used by [`from_row_by_name!`](crate::from_row_by_name))
+///   doesn't care about the order and ignores any additional columns (e.g. from `SELECT *`).
+///   It fits long-lived code whose queries evolve independently.
+pub trait FromRow: Sized {
+    /// Decode the row
+    fn from_row(row: Row) -> Result<Self, Error>;
+}
+
+impl FromRow for Row {
+    fn from_row(row: Row) -> Result<Self, Error> {
+        Ok(row)
+    }
+}
+
+/// Implement [`FromRow`] for tuples decoding their elements by position
+macro_rules! impl_from_row_for_tuple {
+    ($($T:ident => $index:tt),+) => {
+        impl<$($T: DecodeOwned),+> FromRow for ($($T,)+) {
+            fn from_row(row: Row) -> Result<Self, Error> {
+                Ok(($(row.get::<$T, usize>($index)?,)+))
+            }
+        }
+    };
+}
+impl_from_row_for_tuple!(A => 0);
+impl_from_row_for_tuple!(A => 0, B => 1);
+impl_from_row_for_tuple!(A => 0, B => 1, C => 2);
+impl_from_row_for_tuple!(A => 0, B => 1, C => 2, D => 3);
+impl_from_row_for_tuple!(A => 0, B => 1, C => 2, D => 3, E => 4);
+impl_from_row_for_tuple!(A => 0, B => 1, C => 2, D => 3, E => 4, F => 5);
+impl_from_row_for_tuple!(A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6);
+impl_from_row_for_tuple!(A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7);
+
+/// Implements [`FromRow`] for a struct by looking up each field's column by the field's name
+///
+/// The columns' order doesn't matter and additional columns are ignored.
+/// See [`FromRow`] for a comparison with [`from_row_by_position!`](crate::from_row_by_position).
+///
+/// ```skipped
+/// struct User {
+///     id: i64,
+///     name: String,
+/// }
+/// rorm_db::from_row_by_name!(User { id, name });
+/// ```
+#[macro_export]
+macro_rules! from_row_by_name {
+    ($Struct:ident { $($field:ident),* $(,)? }) => {
+        impl $crate::row::FromRow for $Struct {
+            fn from_row(row: $crate::row::Row) -> Result<Self, $crate::Error> {
+                Ok(Self {
+                    $($field: row.get(stringify!($field))?,)*
+                })
+            }
+        }
+    };
+}
+
+/// Implements [`FromRow`] for a struct by decoding its fields from the columns in the listed order
+///
+/// The first listed field is decoded from the first column, the second from the second and so on.
+/// See [`FromRow`] for a comparison with [`from_row_by_name!`](crate::from_row_by_name).
+///
+/// ```skipped
+/// struct User {
+///     id: i64,
+///     name: String,
+/// }
+/// rorm_db::from_row_by_position!(User { id, name });
+/// ```
+#[macro_export]
+macro_rules! from_row_by_position {
+    ($Struct:ident { $($field:ident),* $(,)? }) => {
+        impl $crate::row::FromRow for $Struct {
+            // The last field's increment is never read
+            #[allow(unused_assignments)]
+            fn from_row(row: $crate::row::Row) -> Result<Self, $crate::Error> {
+                let mut index: usize = 0;
+                Ok(Self {
+                    $($field: {
+                        let value = row.get(index)?;
+                        index += 1;
+                        value
+                    },)*
+                })
+            }
+        }
+    };
+}
+
+/// Decodes the row positionally using [`Row::into_values`]
+#[cfg(feature = "sqlx")]
+impl FromRow for Vec<OwnedValue> {
+    fn from_row(row: Row) -> Result<Self, Error> {
+        row.into_values()
+    }
+}
+
+/// Owned and dynamically typed value of a [`Row`]'s cell.
+///
+/// It mirrors the common variants of [`Value`](rorm_sql::value::Value).
+#[cfg(feature = "sqlx")]
+#[derive(Clone, Debug, PartialEq)]
+pub enum OwnedValue {
+    /// SQL `NULL`
+    Null,
+    /// Boolean
+    Bool(bool),
+    /// 16 bit integer
+    I16(i16),
+    /// 32 bit integer
+    I32(i32),
+    /// 64 bit integer
+    I64(i64),
+    /// 32 bit float
+    F32(f32),
+    /// 64 bit float
+    F64(f64),
+    /// String
+    String(String),
+    /// Binary data
+    Binary(Vec<u8>),
+    /// Date without timezone
+    ChronoNaiveDate(chrono::NaiveDate),
+    /// Time without timezone
+    ChronoNaiveTime(chrono::NaiveTime),
+    /// Datetime without timezone
+    ChronoNaiveDateTime(chrono::NaiveDateTime),
+    /// Datetime in UTC
+    ChronoDateTime(chrono::DateTime<chrono::Utc>),
+    /// Uuid
+    Uuid(Uuid),
+    /// Json
+    JsonValue(JsonValue),
 }
 
 impl From<internal::row::Impl> for Row {
@@ -27,7 +388,17 @@ impl From<internal::row::Impl> for Row {
 }
 
 /// Something which can be decoded from a [`Row`]'s cell.
+///
+/// A type has to be decodable by every enabled database driver,
+/// so enabling another driver's feature might make a type unusable.
 #[cfg(feature = "sqlx")]
+#[cfg_attr(
+    DIAGNOSTIC_NAMESPACE,
+    diagnostic::on_unimplemented(
+        message = "`{Self}` can't be decoded from a `Row` by every enabled database driver",
+        note = "`Row::get` requires `sqlx::Type` and `sqlx::Decode` for each enabled driver feature (postgres, mysql, sqlite)"
+    )
+)]
 pub trait Decode<'r>: internal::any::AnyType + internal::any::AnyDecode<'r> {}
 /// Something which can be decoded from a [`Row`]'s cell.
 #[cfg(not(feature = "sqlx"))]
@@ -7,8 +7,12 @@ use std::ops::DerefMut;
 
 use futures::stream::BoxStream;
 use futures::{StreamExt, TryStreamExt};
+use log::debug;
+use sqlx::pool::PoolConnection;
 use sqlx::query::Query;
-use sqlx::{Executor, Pool, Transaction};
+use sqlx::{Executor, Pool, Transaction, TransactionManager};
+
+use crate::executor::DryRunExecutor;
 
 #[macro_use]
 #[path = "./cond_macros.rs"]
@@ -47,6 +51,76 @@ impl AnyPool {
         }
     }
 
+    /// Retrieves a connection without beginning a transaction.
+    ///
+    /// See [`Pool::acquire`]
+    pub async fn acquire(&self) -> sqlx::Result<AnyConnection> {
+        match self {
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => pool.acquire().await.map(AnyConnection::Postgres),
+            #[cfg(feature = "mysql")]
+            Self::MySql(pool) => pool.acquire().await.map(AnyConnection::MySql),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => pool.acquire().await.map(AnyConnection::Sqlite),
+        }
+    }
+
+    /// Retrieves a connection and begins a new transaction using the isolation level `level`.
+    ///
+    /// - Postgres sets the level as first statement inside the transaction.
+    /// - MySQL sets the level for the next transaction before starting it,
+    ///   because it can't be changed inside a transaction.
+    /// - SQLite ignores the level, its transactions are always serializable.
+    ///
+    /// See [`Pool::begin`]
+    pub async fn begin_with_isolation_level(&self, level: &str) -> sqlx::Result<AnyTransaction> {
+        let sql = format!("SET TRANSACTION ISOLATION LEVEL {level}");
+        match self {
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => {
+                let mut tx = pool.begin().await?;
+                tx.deref_mut().execute(sql.as_str()).await?;
+                Ok(AnyTransaction::Postgres(tx))
+            }
+            #[cfg(feature = "mysql")]
+            Self::MySql(pool) => {
+                let mut conn = pool.acquire().await?;
+                conn.deref_mut().execute(sql.as_str()).await?;
+                Transaction::begin(conn).await.map(AnyTransaction::MySql)
+            }
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => pool.begin().await.map(AnyTransaction::Sqlite),
+        }
+    }
+
+    /// Executes a sql string without preparing it i.e. it may contain several statements.
+    ///
+    /// See [`Executor::execute`]
+    pub async fn execute_unprepared(&self, sql: &str) -> sqlx::Result<u64> {
+        match self {
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => pool.execute(sql).await.map(|result| result.rows_affected()),
+            #[cfg(feature = "mysql")]
+            Self::MySql(pool) => pool.execute(sql).await.map(|result| result.rows_affected()),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => pool.execute(sql).await.map(|result| result.rows_affected()),
+        }
+    }
+
+    /// Prepares a sql statement on one of the pool's connections to check it for errors.
+    ///
+    /// See [`Executor::prepare`]
+    pub async fn prepare(&self, sql: &str) -> sqlx::Result<()> {
+        match self {
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => pool.prepare(sql).await.map(drop),
+            #[cfg(feature = "mysql")]
+            Self::MySql(pool) => pool.prepare(sql).await.map(drop),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => pool.prepare(sql).await.map(drop),
+        }
+    }
+
     /// Shut down the connection pool, immediately waking all tasks waiting for a connection.
     ///
     /// See [`Pool::close`]
@@ -75,6 +149,16 @@ impl AnyPool {
     }
 }
 
+/// Enum around [`PoolConnection<DB>`]
+pub enum AnyConnection {
+    #[cfg(feature = "postgres")]
+    Postgres(PoolConnection<Postgres>),
+    #[cfg(feature = "mysql")]
+    MySql(PoolConnection<MySql>),
+    #[cfg(feature = "sqlite")]
+    Sqlite(PoolConnection<Sqlite>),
+}
+
 /// Enum around [`Transaction<'static, DB>`]
 pub enum AnyTransaction {
     #[cfg(feature = "postgres")]
@@ -113,6 +197,62 @@ impl AnyTransaction {
             Self::Sqlite(tx) => tx.rollback().await,
         }
     }
+
+    /// Creates a savepoint inside this transaction.
+    ///
+    /// See [`TransactionManager::begin`]
+    pub async fn begin_nested(&mut self) -> sqlx::Result<()> {
+        match self {
+            #[cfg(feature = "postgres")]
+            Self::Postgres(tx) => postgres::PgTransactionManager::begin(tx.deref_mut()).await,
+            #[cfg(feature = "mysql")]
+            Self::MySql(tx) => mysql::MySqlTransactionManager::begin(tx.deref_mut()).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(tx) => sqlite::SqliteTransactionManager::begin(tx.deref_mut()).await,
+        }
+    }
+
+    /// Releases the innermost savepoint.
+    ///
+    /// See [`TransactionManager::commit`]
+    pub async fn commit_nested(&mut self) -> sqlx::Result<()> {
+        match self {
+            #[cfg(feature = "postgres")]
+            Self::Postgres(tx) => postgres::PgTransactionManager::commit(tx.deref_mut()).await,
+            #[cfg(feature = "mysql")]
+            Self::MySql(tx) => mysql::MySqlTransactionManager::commit(tx.deref_mut()).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(tx) => sqlite::SqliteTransactionManager::commit(tx.deref_mut()).await,
+        }
+    }
+
+    /// Rolls back to the innermost savepoint.
+    ///
+    /// See [`TransactionManager::rollback`]
+    pub async fn rollback_nested(&mut self) -> sqlx::Result<()> {
+        match self {
+            #[cfg(feature = "postgres")]
+            Self::Postgres(tx) => postgres::PgTransactionManager::rollback(tx.deref_mut()).await,
+            #[cfg(feature = "mysql")]
+            Self::MySql(tx) => mysql::MySqlTransactionManager::rollback(tx.deref_mut()).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(tx) => sqlite::SqliteTransactionManager::rollback(tx.deref_mut()).await,
+        }
+    }
+
+    /// Queues a rollback to the innermost savepoint which is executed before the next query.
+    ///
+    /// See [`TransactionManager::start_rollback`]
+    pub fn start_rollback_nested(&mut self) {
+        match self {
+            #[cfg(feature = "postgres")]
+            Self::Postgres(tx) => postgres::PgTransactionManager::start_rollback(tx.deref_mut()),
+            #[cfg(feature = "mysql")]
+            Self::MySql(tx) => mysql::MySqlTransactionManager::start_rollback(tx.deref_mut()),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(tx) => sqlite::SqliteTransactionManager::start_rollback(tx.deref_mut()),
+        }
+    }
 }
 
 /// Combination of an [`AnyExecutor`] and its associated [`Query<'q, DB, _>`]
@@ -129,6 +269,8 @@ pub enum AnyQuery<'q> {
     MySqlConn(AnyQueryInner<'q, &'q mut mysql::MySqlConnection, mysql::MySqlArguments>),
     #[cfg(feature = "sqlite")]
     SqliteConn(AnyQueryInner<'q, &'q mut sqlite::SqliteConnection, sqlite::SqliteArguments<'q>>),
+    /// Query captured by a [`DryRunExecutor`] which doesn't return anything
+    DryRun,
 }
 #[doc(hidden)]
 pub struct AnyQueryInner<'q, E: Executor<'q>, A> {
@@ -160,9 +302,15 @@ impl<'q> AnyQuery<'q> {
             | Self::SqliteConn(AnyQueryInner { query, .. }) => {
                 *query = query.take().map(|query| query.bind(value))
             }
+            Self::DryRun => {}
         }
     }
 
+    /// Whether this query has been captured by a [`DryRunExecutor`]
+    pub fn is_dry_run(&self) -> bool {
+        matches!(self, Self::DryRun)
+    }
+
     /// Execute the query and return the generated results in a stream.
     pub fn fetch_many(self) -> BoxStream<'q, sqlx::Result<sqlx::Either<AnyQueryResult, AnyRow>>> {
         macro_rules! match_impl {
@@ -176,7 +324,9 @@ impl<'q> AnyQuery<'q> {
                                 .map_right(AnyRow::$db)
                         })
                         .boxed(),
-                )+}
+                )+
+                    Self::DryRun => futures::stream::empty().boxed(),
+                }
             }
         }
         expand_fetch_impl!(match_impl)
@@ -196,7 +346,9 @@ impl<'q> AnyQuery<'q> {
                         })
                         .try_collect()
                         .await,
-                )+}
+                )+
+                    Self::DryRun => Ok(Vec::new()),
+                }
             }
         }
         expand_fetch_impl!(match_impl)
@@ -211,7 +363,9 @@ impl<'q> AnyQuery<'q> {
                         .fetch_optional(query.unwrap())
                         .await
                         .map(|option| option.map(AnyRow::$db)),
-                )+}
+                )+
+                    Self::DryRun => Ok(None),
+                }
             }
         }
         expand_fetch_impl!(match_impl)
@@ -224,15 +378,24 @@ impl<'q> AnyQuery<'q> {
                 match self {$(
                     Self::$variant(AnyQueryInner { executor, query }) => executor
                         .fetch_many(query.unwrap())
-                        .try_fold(0, |sum, either| async move {Ok(match either {
-                            sqlx::Either::Left(result) => sum + result.rows_affected(),
-                            sqlx::Either::Right(_) => sum,
+                        .try_fold((0, 0), |(affected, returned), either| async move {Ok(match either {
+                            sqlx::Either::Left(result) => (affected + result.rows_affected(), returned),
+                            sqlx::Either::Right(_) => (affected, returned + 1),
                         })})
                         .await,
-                )+}
+                )+
+                    Self::DryRun => Ok((0, 0)),
+                }
             }
         }
-        expand_fetch_impl!(match_impl)
+        let (affected, returned): (u64, u64) = expand_fetch_impl!(match_impl)?;
+        if returned > 0 {
+            debug!(
+                "A query executed for its affected rows returned {returned} rows which have been discarded. \
+                Did you mean to use a different query strategy?"
+            );
+        }
+        Ok(affected)
     }
 }
 
@@ -268,6 +431,25 @@ impl AnyQueryResult {
             Self::Sqlite(result) => result.rows_affected(),
         }
     }
+
+    /// The id generated for an auto increment column by an insert
+    ///
+    /// Postgres doesn't report it and always returns `None`, use `RETURNING` instead.
+    /// MySQL reports `0` if the query didn't generate an id, which is returned as `None` as well.
+    /// SQLite reports the rowid of the last successful insert on the connection.
+    pub fn last_insert_id(&self) -> Option<i64> {
+        match self {
+            #[cfg(feature = "postgres")]
+            Self::Postgres(_) => None,
+            #[cfg(feature = "mysql")]
+            Self::MySql(result) => match result.last_insert_id() {
+                0 => None,
+                id => i64::try_from(id).ok(),
+            },
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(result) => Some(result.last_insert_rowid()),
+        }
+    }
 }
 
 /// Trait to start queries from either an [`AnyPool`] or an [`AnyTransaction`]
@@ -280,6 +462,15 @@ pub trait AnyExecutor<'e> {
     where
         'e: 'q;
 }
+impl<'e> AnyExecutor<'e> for &'e DryRunExecutor {
+    fn query<'q>(self, _query: &'q str) -> AnyQuery<'q>
+    where
+        'e: 'q,
+    {
+        AnyQuery::DryRun
+    }
+}
+
 impl<'e> AnyExecutor<'e> for &'e AnyPool {
     fn query<'q>(self, query: &'q str) -> AnyQuery<'q>
     where
@@ -328,6 +519,30 @@ impl<'e> AnyExecutor<'e> for &'e mut AnyTransaction {
         }
     }
 }
+impl<'e> AnyExecutor<'e> for &'e mut AnyConnection {
+    fn query<'q>(self, query: &'q str) -> AnyQuery<'q>
+    where
+        'e: 'q,
+    {
+        match self {
+            #[cfg(feature = "postgres")]
+            AnyConnection::Postgres(conn) => AnyQuery::PostgresConn(AnyQueryInner {
+                executor: conn.deref_mut(),
+                query: Some(sqlx::query(query)),
+            }),
+            #[cfg(feature = "mysql")]
+            AnyConnection::MySql(conn) => AnyQuery::MySqlConn(AnyQueryInner {
+                executor: conn.deref_mut(),
+                query: Some(sqlx::query(query)),
+            }),
+            #[cfg(feature = "sqlite")]
+            AnyConnection::Sqlite(conn) => AnyQuery::SqliteConn(AnyQueryInner {
+                executor: conn.deref_mut(),
+                query: Some(sqlx::query(query)),
+            }),
+        }
+    }
+}
 
 macro_rules! uncond_trait_alias {
     ($(#[doc = $doc:literal])* trait $trait:ident $(<$lifetime:lifetime>)?: $($bound:path,)+) => {
@@ -0,0 +1,40 @@
+use std::future::Future;
+
+use crate::connection::Connection;
+use crate::internal::any::AnyConnection;
+
+pub(crate) type Impl = AnyConnection;
+
+/// Used by [AdvisoryLock::drop](crate::database::AdvisoryLock) to release the lock in the background
+///
+/// `connection` is handed back if there is no tokio runtime to spawn `task` on.
+pub fn spawn<F>(
+    connection: Connection,
+    task: impl FnOnce(Connection) -> F,
+) -> Result<(), Connection>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    #[cfg(feature = "tokio")]
+    if let Ok(handle) = tokio::runtime::Handle::try_current() {
+        handle.spawn(task(connection));
+        return Ok(());
+    }
+    #[cfg(not(feature = "tokio"))]
+    let _ = task;
+    Err(connection)
+}
+
+/// Closes `connection` instead of returning it to the pool
+///
+/// This ends the connection's session which releases every lock it holds.
+pub fn close(connection: Impl) {
+    match connection {
+        #[cfg(feature = "postgres")]
+        AnyConnection::Postgres(connection) => drop(connection.detach()),
+        #[cfg(feature = "mysql")]
+        AnyConnection::MySql(connection) => drop(connection.detach()),
+        #[cfg(feature = "sqlite")]
+        AnyConnection::Sqlite(connection) => drop(connection.detach()),
+    }
+}
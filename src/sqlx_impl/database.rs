@@ -1,26 +1,29 @@
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
 use std::time::Duration;
 
 use futures::TryStreamExt;
-use log::{debug, LevelFilter};
+use log::debug;
 use rorm_declaration::config::DatabaseDriver;
 use rorm_sql::value::Value;
-use sqlx::ConnectOptions;
+use sqlx::{ConnectOptions, Connection as _, Executor as _};
 
-use crate::database::{Database, DatabaseConfiguration};
+#[cfg(any(feature = "postgres", feature = "mysql"))]
+use crate::database::SslMode;
+use crate::database::{Database, DatabaseConfiguration, IsolationLevel};
 use crate::error::Error;
+use crate::internal;
 use crate::internal::any::{AnyExecutor, AnyPool};
+use crate::internal::executor::StatementLog;
 use crate::internal::utils;
 use crate::row::Row;
 use crate::transaction::Transaction;
 
 pub(crate) type Impl = AnyPool;
 
-/// All statements that take longer to execute than this value are considered
-/// as slow statements.
-const SLOW_STATEMENTS: Duration = Duration::from_millis(300);
-
 /// Implementation of [Database::connect]
-pub(crate) async fn connect(configuration: DatabaseConfiguration) -> Result<Impl, Error> {
+pub(crate) async fn connect(configuration: &DatabaseConfiguration) -> Result<Impl, Error> {
     if configuration.max_connections < configuration.min_connections {
         return Err(Error::ConfigurationError(String::from(
             "max_connections must not be less than min_connections",
@@ -34,21 +37,35 @@ pub(crate) async fn connect(configuration: DatabaseConfiguration) -> Result<Impl
     }
 
     macro_rules! pool_options {
-        ($Pool:ty) => {
-            <$Pool>::new()
+        ($Pool:ty) => {{
+            let mut pool_options = <$Pool>::new()
                 .min_connections(configuration.min_connections)
-                .max_connections(configuration.max_connections)
-        };
+                .max_connections(configuration.max_connections);
+            if let Some(sql) = &configuration.before_acquire {
+                let sql: Arc<str> = Arc::from(sql.as_str());
+                pool_options = pool_options.before_acquire(move |connection, _| {
+                    let sql = sql.clone();
+                    Box::pin(async move {
+                        connection.execute(&*sql).await?;
+                        Ok(true)
+                    })
+                });
+            }
+            if let Some(sql) = &configuration.after_release {
+                let sql: Arc<str> = Arc::from(sql.as_str());
+                pool_options = pool_options.after_release(move |connection, _| {
+                    let sql = sql.clone();
+                    Box::pin(async move {
+                        connection.execute(&*sql).await?;
+                        connection.clear_cached_statements().await?;
+                        Ok(true)
+                    })
+                });
+            }
+            pool_options
+        }};
     }
 
-    let slow_log_level = configuration
-        .slow_statement_log_level
-        .unwrap_or(LevelFilter::Warn);
-    let log_level = configuration
-        .statement_log_level
-        .unwrap_or(LevelFilter::Debug);
-    let disabled_logging = configuration.disable_logging.unwrap_or(false);
-
     let pool: Impl = match &configuration.driver {
         #[cfg(feature = "sqlite")]
         DatabaseDriver::SQLite { filename } => {
@@ -60,13 +77,8 @@ pub(crate) async fn connect(configuration: DatabaseConfiguration) -> Result<Impl
             let connect_options = sqlx::sqlite::SqliteConnectOptions::new()
                 .create_if_missing(true)
                 .filename(filename);
-            let connect_options = if disabled_logging {
-                connect_options.disable_statement_logging()
-            } else {
-                connect_options
-                    .log_statements(log_level)
-                    .log_slow_statements(slow_log_level, SLOW_STATEMENTS)
-            };
+            // Statements are timed and logged by our executors to allow per handle log levels
+            let connect_options = connect_options.disable_statement_logging();
             Impl::Sqlite(
                 pool_options!(sqlx::sqlite::SqlitePoolOptions)
                     .connect_with(connect_options)
@@ -87,23 +99,39 @@ pub(crate) async fn connect(configuration: DatabaseConfiguration) -> Result<Impl
                 )));
             }
             let connect_options = sqlx::postgres::PgConnectOptions::new()
-                .host(host.as_str())
-                .port(*port)
                 .username(user.as_str())
                 .password(password.as_str())
                 .database(name.as_str());
-            let connect_options = if disabled_logging {
-                connect_options.disable_statement_logging()
-            } else {
-                connect_options
-                    .log_statements(log_level)
-                    .log_slow_statements(slow_log_level, SLOW_STATEMENTS)
+            let connect_options = match &configuration.application_name {
+                Some(application_name) => connect_options.application_name(application_name),
+                None => connect_options,
             };
-            Impl::Postgres(
-                pool_options!(sqlx::postgres::PgPoolOptions)
-                    .connect_with(connect_options)
-                    .await?,
-            )
+            let connect_options = postgres_tls(connect_options, configuration);
+            // Statements are timed and logged by our executors to allow per handle log levels
+            let connect_options = connect_options.disable_statement_logging();
+            // sqlx accepts a single host only, so the candidates are tried one after another
+            let mut result = Err(Error::ConfigurationError(String::from(
+                "host must not be empty",
+            )));
+            for (host, port) in postgres_hosts(host, *port)? {
+                // Postgres expects the directory containing the socket `.s.PGSQL.<port>`
+                let connect_options = if is_socket_path(host) {
+                    connect_options.clone().socket(host)
+                } else {
+                    connect_options.clone().host(host)
+                };
+                result = pool_options!(sqlx::postgres::PgPoolOptions)
+                    .connect_with(connect_options.port(port))
+                    .await
+                    .map_err(Error::from);
+                match &result {
+                    Ok(_) => break,
+                    Err(error) => {
+                        debug!("Failed to connect to postgres host {host}:{port}: {error}")
+                    }
+                }
+            }
+            Impl::Postgres(result?)
         }
         #[cfg(feature = "mysql")]
         DatabaseDriver::MySQL {
@@ -118,19 +146,21 @@ pub(crate) async fn connect(configuration: DatabaseConfiguration) -> Result<Impl
                     "name must not be empty",
                 )));
             }
-            let connect_options = sqlx::mysql::MySqlConnectOptions::new()
-                .host(host.as_str())
+            let connect_options = sqlx::mysql::MySqlConnectOptions::new();
+            // MySQL expects the path of the socket itself
+            let connect_options = if is_socket_path(host) {
+                connect_options.socket(host)
+            } else {
+                connect_options.host(host.as_str())
+            };
+            let connect_options = connect_options
                 .port(*port)
                 .username(user.as_str())
                 .password(password.as_str())
                 .database(name.as_str());
-            let connect_options = if disabled_logging {
-                connect_options.disable_statement_logging()
-            } else {
-                connect_options
-                    .log_statements(log_level)
-                    .log_slow_statements(slow_log_level, SLOW_STATEMENTS)
-            };
+            let connect_options = mysql_tls(connect_options, configuration);
+            // Statements are timed and logged by our executors to allow per handle log levels
+            let connect_options = connect_options.disable_statement_logging();
             Impl::MySql(
                 pool_options!(sqlx::mysql::MySqlPoolOptions)
                     .connect_with(connect_options)
@@ -142,6 +172,165 @@ pub(crate) async fn connect(configuration: DatabaseConfiguration) -> Result<Impl
     Ok(pool)
 }
 
+/// Check whether a configured host is actually the path of a unix domain socket
+///
+/// See [`DatabaseConfiguration::driver`]
+#[cfg(any(feature = "postgres", feature = "mysql"))]
+fn is_socket_path(host: &str) -> bool {
+    host.starts_with('/')
+}
+
+/// Splits postgres' comma separated `host` into its candidates
+///
+/// Every candidate may specify its own port using `host:port`, otherwise `port` is used.
+/// Candidates containing more than one `:` are IPv6 addresses without a port.
+#[cfg(feature = "postgres")]
+fn postgres_hosts(host: &str, port: u16) -> Result<Vec<(&str, u16)>, Error> {
+    host.split(',')
+        .map(str::trim)
+        .map(|candidate| {
+            if candidate.is_empty() {
+                return Err(Error::ConfigurationError(String::from(
+                    "host must not contain empty entries",
+                )));
+            }
+            match candidate.split_once(':') {
+                Some((host, candidate_port)) if !candidate_port.contains(':') => {
+                    let port = candidate_port.parse().map_err(|_| {
+                        Error::ConfigurationError(format!("invalid port in host {candidate:?}"))
+                    })?;
+                    Ok((host, port))
+                }
+                _ => Ok((candidate, port)),
+            }
+        })
+        .collect()
+}
+
+/// Apply the TLS settings to postgres' connect options
+#[cfg(feature = "postgres")]
+fn postgres_tls(
+    mut connect_options: sqlx::postgres::PgConnectOptions,
+    configuration: &DatabaseConfiguration,
+) -> sqlx::postgres::PgConnectOptions {
+    use sqlx::postgres::PgSslMode;
+
+    if let Some(ssl_mode) = configuration.ssl_mode {
+        connect_options = connect_options.ssl_mode(match ssl_mode {
+            SslMode::Disable => PgSslMode::Disable,
+            SslMode::Prefer => PgSslMode::Prefer,
+            SslMode::Require => PgSslMode::Require,
+            SslMode::VerifyCa => PgSslMode::VerifyCa,
+            SslMode::VerifyFull => PgSslMode::VerifyFull,
+        });
+    }
+    if let Some(path) = &configuration.ssl_root_cert {
+        connect_options = connect_options.ssl_root_cert(path);
+    }
+    if let Some(path) = &configuration.ssl_client_cert {
+        connect_options = connect_options.ssl_client_cert(path);
+    }
+    if let Some(path) = &configuration.ssl_client_key {
+        connect_options = connect_options.ssl_client_key(path);
+    }
+    connect_options
+}
+
+/// Apply the TLS settings to mysql's connect options
+#[cfg(feature = "mysql")]
+fn mysql_tls(
+    mut connect_options: sqlx::mysql::MySqlConnectOptions,
+    configuration: &DatabaseConfiguration,
+) -> sqlx::mysql::MySqlConnectOptions {
+    use sqlx::mysql::MySqlSslMode;
+
+    if let Some(ssl_mode) = configuration.ssl_mode {
+        connect_options = connect_options.ssl_mode(match ssl_mode {
+            SslMode::Disable => MySqlSslMode::Disabled,
+            SslMode::Prefer => MySqlSslMode::Preferred,
+            SslMode::Require => MySqlSslMode::Required,
+            SslMode::VerifyCa => MySqlSslMode::VerifyCa,
+            SslMode::VerifyFull => MySqlSslMode::VerifyIdentity,
+        });
+    }
+    if let Some(path) = &configuration.ssl_root_cert {
+        connect_options = connect_options.ssl_ca(path);
+    }
+    if let Some(path) = &configuration.ssl_client_cert {
+        connect_options = connect_options.ssl_client_cert(path);
+    }
+    if let Some(path) = &configuration.ssl_client_key {
+        connect_options = connect_options.ssl_client_key(path);
+    }
+    connect_options
+}
+
+/// Checked in [Database::connect_with_retry] to decide whether to retry
+pub fn is_transient(error: &Error) -> bool {
+    match error {
+        Error::SqlxError(sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut) => true,
+        // Postgres' `cannot_connect_now` is returned while the server is starting up
+        Error::SqlxError(sqlx::Error::Database(error)) => error.code().as_deref() == Some("57P03"),
+        _ => false,
+    }
+}
+
+/// Used by [Database::connect_with_retry] to wait between attempts
+pub async fn sleep(duration: Duration) {
+    #[cfg(feature = "tokio")]
+    tokio::time::sleep(duration).await;
+    #[cfg(all(feature = "async-std", not(feature = "tokio")))]
+    async_std::task::sleep(duration).await;
+    #[cfg(not(any(feature = "tokio", feature = "async-std")))]
+    missing_runtime(duration);
+}
+
+/// Used by [Database::execute_file] to read the script without blocking the runtime
+#[cfg(feature = "tokio")]
+pub async fn read_to_string(path: &Path) -> Result<String, Error> {
+    Ok(tokio::fs::read_to_string(path).await?)
+}
+
+/// Used by [Database::execute_file] to read the script without blocking the runtime
+#[cfg(all(feature = "async-std", not(feature = "tokio")))]
+pub async fn read_to_string(path: &Path) -> Result<String, Error> {
+    Ok(async_std::fs::read_to_string(path).await?)
+}
+
+/// Used by [Database::execute_file] to read the script without blocking the runtime
+#[cfg(not(any(feature = "tokio", feature = "async-std")))]
+pub async fn read_to_string(path: &Path) -> Result<String, Error> {
+    missing_runtime(path)
+}
+
+/// Used by [Database::start_transaction] to limit the time spent waiting for a connection
+#[cfg(feature = "tokio")]
+async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Output, Error> {
+    tokio::time::timeout(duration, future)
+        .await
+        .map_err(|_| Error::Timeout)
+}
+
+/// Used by [Database::start_transaction] to limit the time spent waiting for a connection
+#[cfg(all(feature = "async-std", not(feature = "tokio")))]
+async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Output, Error> {
+    async_std::future::timeout(duration, future)
+        .await
+        .map_err(|_| Error::Timeout)
+}
+
+/// Used by [Database::start_transaction] to limit the time spent waiting for a connection
+#[cfg(not(any(feature = "tokio", feature = "async-std")))]
+async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Output, Error> {
+    missing_runtime((duration, future))
+}
+
+/// Panics because sqlx has been enabled without a runtime to drive it
+#[cfg(not(any(feature = "tokio", feature = "async-std")))]
+fn missing_runtime<T>(_: T) -> ! {
+    panic!("Either the tokio or the async-std feature is required to use timers or files")
+}
+
 /// Implementation of [Database::raw_sql]
 pub async fn raw_sql<'a>(
     db: &Database,
@@ -159,7 +348,7 @@ pub async fn raw_sql<'a>(
 
     if let Some(params) = bind_params {
         for param in params {
-            utils::bind_param(&mut query, *param);
+            utils::bind_param(&mut query, *param)?;
         }
     }
 
@@ -171,9 +360,110 @@ pub async fn raw_sql<'a>(
         .await
 }
 
+/// Implementation of [Database::execute_unprepared]
+pub async fn execute_unprepared(db: &Database, sql: &str) -> Result<u64, Error> {
+    let mut log = StatementLog::new(&db.2);
+    log.start();
+    let result = db.0.execute_unprepared(sql).await;
+    log.finish(sql);
+    Ok(result?)
+}
+
+/// Implementation of [Database::execute_batch]
+pub async fn execute_batch(db: &Database, sql: &str) -> Result<(), Error> {
+    #[cfg(feature = "mysql")]
+    if matches!(db.0, AnyPool::MySql(_)) && !db.2.allow_multiple_statements {
+        return Err(Error::ConfigurationError(String::from(
+            "executing a batch on mysql requires allow_multiple_statements",
+        )));
+    }
+
+    let mut log = StatementLog::new(&db.2);
+    log.start();
+    let result = db.0.execute_unprepared(sql).await;
+    log.finish(sql);
+    result?;
+    Ok(())
+}
+
+/// Implementation of [Database::prepare]
+pub async fn prepare(db: &Database, sql: &str) -> Result<(), Error> {
+    Ok(db.0.prepare(sql).await?)
+}
+
 /// Implementation of [Database::start_transaction]
-pub async fn start_transaction(db: &Database) -> Result<Transaction, Error> {
-    Ok(Transaction(db.0.begin().await?))
+pub async fn start_transaction(
+    db: &Database,
+    isolation_level: Option<IsolationLevel>,
+) -> Result<internal::transaction::Impl, Error> {
+    let begin = async {
+        match isolation_level {
+            Some(isolation_level) => {
+                db.0.begin_with_isolation_level(isolation_level.as_sql())
+                    .await
+            }
+            None => db.0.begin().await,
+        }
+    };
+    Ok(match db.2.begin_timeout {
+        Some(duration) => timeout(duration, begin).await??,
+        None => begin.await?,
+    })
+}
+
+/// Implementation of [Database::with_connection]
+pub async fn acquire(db: &Database) -> Result<internal::connection::Impl, Error> {
+    Ok(db.0.acquire().await?)
+}
+
+/// Implementation of [copy_out](crate::database::copy_out)
+#[cfg(feature = "postgres")]
+pub fn copy_out(
+    executor: crate::executor::DynamicExecutor<'_>,
+    statement: String,
+) -> futures::stream::BoxStream<'_, Result<Vec<u8>, Error>> {
+    use std::ops::DerefMut;
+
+    use futures::{stream, StreamExt};
+    use sqlx::postgres::PgPoolCopyExt;
+
+    use crate::connection::Connection;
+    use crate::executor::DynamicExecutor;
+    use crate::internal::any::{AnyConnection, AnyTransaction};
+
+    let not_postgres =
+        || Error::ConfigurationError(String::from("copy_out is only supported on postgres"));
+    stream::once(async move {
+        let chunks = match executor {
+            DynamicExecutor::Database(db) => {
+                db.check_draining()?;
+                match &db.0 {
+                    AnyPool::Postgres(pool) => pool.copy_out_raw(&statement).await?,
+                    #[allow(unreachable_patterns)]
+                    _ => return Err(not_postgres()),
+                }
+            }
+            DynamicExecutor::Transaction(Transaction(tx, ..)) => match tx {
+                AnyTransaction::Postgres(tx) => tx.deref_mut().copy_out_raw(&statement).await?,
+                #[allow(unreachable_patterns)]
+                _ => return Err(not_postgres()),
+            },
+            DynamicExecutor::Connection(Connection(conn, ..)) => match conn {
+                AnyConnection::Postgres(conn) => conn.deref_mut().copy_out_raw(&statement).await?,
+                #[allow(unreachable_patterns)]
+                _ => return Err(not_postgres()),
+            },
+            DynamicExecutor::DryRun(dry_run) => {
+                dry_run.capture(&statement, &[]);
+                stream::empty().boxed()
+            }
+        };
+        Ok(chunks
+            .map_ok(|chunk| chunk.to_vec())
+            .map_err(Error::SqlxError))
+    })
+    .try_flatten()
+    .boxed()
 }
 
 /// Implementation of [Database::close]
@@ -181,7 +471,33 @@ pub async fn close(db: Database) {
     db.0.close().await;
 }
 
+/// Implementation of [Database::drain_complete]
+pub async fn drain(db: &Database) {
+    // sqlx waits for checked out connections to be returned
+    db.0.close().await;
+}
+
 /// Checked in [Database::drop]
 pub fn is_closed(db: &Database) -> bool {
     db.0.is_closed()
 }
+
+#[cfg(all(test, feature = "postgres"))]
+mod test {
+    use super::postgres_hosts;
+
+    #[test]
+    fn test_postgres_hosts() {
+        assert_eq!(
+            postgres_hosts("localhost", 5432).unwrap(),
+            [("localhost", 5432)]
+        );
+        assert_eq!(
+            postgres_hosts("primary, replica:5433", 5432).unwrap(),
+            [("primary", 5432), ("replica", 5433)]
+        );
+        assert_eq!(postgres_hosts("::1", 5432).unwrap(), [("::1", 5432)]);
+        assert!(postgres_hosts("primary,", 5432).is_err());
+        assert!(postgres_hosts("primary:port", 5432).is_err());
+    }
+}
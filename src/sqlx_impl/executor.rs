@@ -1,19 +1,25 @@
 use std::future::{ready, Ready};
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 
 use futures::future::{self, BoxFuture, FutureExt, TryFutureExt};
 use futures::stream::{self, BoxStream, TryCollect, TryFilterMap, TryStreamExt};
+use log::{log, LevelFilter};
 use rorm_sql::value::Value;
 use rorm_sql::DBImpl;
+use sqlx::types::JsonValue;
 
+use crate::connection::Connection;
+use crate::database::{DatabaseSettings, StatementLogFormat};
 use crate::executor::{
-    AffectedRows, All, DynamicExecutor, Executor, Nothing, One, Optional, QueryStrategy,
-    QueryStrategyResult, Stream,
+    AffectedRows, All, AllWithAffected, AtMostOne, DryRunExecutor, DynamicExecutor, ExactlyOne,
+    Executor, MultiResultSet, Nothing, One, Optional, QueryStrategy, QueryStrategyResult, Stream,
 };
-use crate::internal::any::{AnyExecutor, AnyPool, AnyQueryResult, AnyRow, AnyTransaction};
+use crate::internal::any::{AnyConnection, AnyExecutor, AnyPool, AnyQuery, AnyQueryResult, AnyRow};
 use crate::transaction::{Transaction, TransactionGuard};
-use crate::{Database, Error, Row};
+use crate::{internal, Database, Error, Row};
 
 impl<'executor> Executor<'executor> for &'executor mut Transaction {
     fn execute<'data, 'result, Q>(
@@ -26,7 +32,10 @@ impl<'executor> Executor<'executor> for &'executor mut Transaction {
         'data: 'result,
         Q: QueryStrategy,
     {
-        Q::execute(&mut self.0, query, values)
+        self.3.dirty = true;
+        // The hint is added first, so a rewriter prepending a comment can't hide the `SELECT`
+        let query = self.2.rewrite_query(self.add_execution_time_hint(query));
+        Q::execute(Ok(&mut self.0), query, values, StatementLog::new(&self.2))
     }
 
     fn into_dyn(self) -> DynamicExecutor<'executor> {
@@ -34,14 +43,7 @@ impl<'executor> Executor<'executor> for &'executor mut Transaction {
     }
 
     fn dialect(&self) -> DBImpl {
-        match self.0 {
-            #[cfg(feature = "postgres")]
-            AnyTransaction::Postgres(_) => DBImpl::Postgres,
-            #[cfg(feature = "mysql")]
-            AnyTransaction::MySql(_) => DBImpl::MySQL,
-            #[cfg(feature = "sqlite")]
-            AnyTransaction::Sqlite(_) => DBImpl::SQLite,
-        }
+        internal::transaction::dialect(self)
     }
 
     type EnsureTransactionFuture = Ready<Result<TransactionGuard<'executor>, Error>>;
@@ -64,7 +66,9 @@ impl<'executor> Executor<'executor> for &'executor Database {
         'data: 'result,
         Q: QueryStrategy,
     {
-        Q::execute(&self.0, query, values)
+        let query = self.2.rewrite_query(query);
+        let log = StatementLog::new(&self.2);
+        Q::execute(self.check_draining().map(|()| &self.0), query, values, log)
     }
 
     fn into_dyn(self) -> DynamicExecutor<'executor> {
@@ -91,11 +95,172 @@ impl<'executor> Executor<'executor> for &'executor Database {
     }
 }
 
+impl<'executor> Executor<'executor> for &'executor mut Connection {
+    fn execute<'data, 'result, Q>(
+        self,
+        query: String,
+        values: Vec<Value<'data>>,
+    ) -> Q::Result<'result>
+    where
+        'executor: 'result,
+        'data: 'result,
+        Q: QueryStrategy,
+    {
+        let query = self.1.rewrite_query(query);
+        Q::execute(Ok(&mut self.0), query, values, StatementLog::new(&self.1))
+    }
+
+    fn into_dyn(self) -> DynamicExecutor<'executor> {
+        DynamicExecutor::Connection(self)
+    }
+
+    fn dialect(&self) -> DBImpl {
+        match self.0 {
+            #[cfg(feature = "postgres")]
+            AnyConnection::Postgres(_) => DBImpl::Postgres,
+            #[cfg(feature = "mysql")]
+            AnyConnection::MySql(_) => DBImpl::MySQL,
+            #[cfg(feature = "sqlite")]
+            AnyConnection::Sqlite(_) => DBImpl::SQLite,
+        }
+    }
+
+    type EnsureTransactionFuture = Ready<Result<TransactionGuard<'executor>, Error>>;
+
+    fn ensure_transaction(
+        self,
+    ) -> BoxFuture<'executor, Result<TransactionGuard<'executor>, Error>> {
+        Box::pin(ready(Err(Error::ConfigurationError(String::from(
+            "a connection can't start a transaction, use Database::start_transaction instead",
+        )))))
+    }
+}
+
+impl<'executor> Executor<'executor> for &'executor DryRunExecutor {
+    fn execute<'data, 'result, Q>(
+        self,
+        query: String,
+        values: Vec<Value<'data>>,
+    ) -> Q::Result<'result>
+    where
+        'executor: 'result,
+        'data: 'result,
+        Q: QueryStrategy,
+    {
+        self.capture(&query, &values);
+        Q::execute(Ok(self), query, values, StatementLog::disabled())
+    }
+
+    fn into_dyn(self) -> DynamicExecutor<'executor> {
+        DynamicExecutor::DryRun(self)
+    }
+
+    fn dialect(&self) -> DBImpl {
+        self.dialect
+    }
+
+    type EnsureTransactionFuture = Ready<Result<TransactionGuard<'executor>, Error>>;
+
+    fn ensure_transaction(
+        self,
+    ) -> BoxFuture<'executor, Result<TransactionGuard<'executor>, Error>> {
+        Box::pin(ready(Ok(TransactionGuard::DryRun(self))))
+    }
+}
+
+/// Times a statement and logs it using the handle's settings once it has been executed
+///
+/// Statements taking at least the [`slow_statement_threshold`](crate::DatabaseConfiguration::slow_statement_threshold)
+/// are logged at the [`slow_statement_log_level`](crate::DatabaseConfiguration::slow_statement_log_level)
+/// instead of the handle's [`statement_log_level`](crate::DatabaseConfiguration::statement_log_level).
+pub(crate) struct StatementLog {
+    level: LevelFilter,
+    slow_level: LevelFilter,
+    slow_threshold: Duration,
+    format: StatementLogFormat,
+    pool_name: Option<Arc<str>>,
+
+    /// Set when the statement is started and taken when it is logged
+    started: Option<Instant>,
+}
+
+impl StatementLog {
+    pub(crate) fn new(settings: &DatabaseSettings) -> Self {
+        Self {
+            level: settings.statement_log_level,
+            slow_level: settings.slow_statement_log_level,
+            slow_threshold: settings.slow_statement_threshold,
+            format: settings.statement_log_format,
+            pool_name: settings.pool_name.clone(),
+            started: None,
+        }
+    }
+
+    /// A log which never writes anything, used for dry runs
+    pub(crate) fn disabled() -> Self {
+        Self {
+            level: LevelFilter::Off,
+            slow_level: LevelFilter::Off,
+            slow_threshold: Duration::MAX,
+            format: StatementLogFormat::Plain,
+            pool_name: None,
+            started: None,
+        }
+    }
+
+    /// Start timing the statement unless it has already been started
+    pub(crate) fn start(&mut self) {
+        self.started.get_or_insert_with(Instant::now);
+    }
+
+    /// Log the statement with the time elapsed since it has been started
+    ///
+    /// Statements which have never been started or have already been logged are ignored.
+    pub(crate) fn finish(&mut self, query: &str) {
+        let Some(started) = self.started.take() else {
+            return;
+        };
+        let elapsed = started.elapsed();
+        let level = if elapsed >= self.slow_threshold {
+            self.slow_level
+        } else {
+            self.level
+        };
+        let Some(level) = level.to_level() else {
+            return;
+        };
+        let elapsed_ms = elapsed.as_millis();
+        match self.format {
+            StatementLogFormat::Plain => {
+                let prefix = match &self.pool_name {
+                    Some(pool_name) => format!("[{pool_name}] "),
+                    None => String::new(),
+                };
+                log!(target: "rorm_db::query", level, "{prefix}{query} ({elapsed_ms} ms)")
+            }
+            StatementLogFormat::Json => {
+                // `JsonValue`'s `Display` takes care of escaping
+                let sql = JsonValue::from(query);
+                match &self.pool_name {
+                    Some(pool_name) => {
+                        let pool_name = JsonValue::from(&**pool_name);
+                        log!(target: "rorm_db::query", level, r#"{{"sql": {sql}, "elapsed_ms": {elapsed_ms}, "pool": {pool_name}}}"#)
+                    }
+                    None => {
+                        log!(target: "rorm_db::query", level, r#"{{"sql": {sql}, "elapsed_ms": {elapsed_ms}}}"#)
+                    }
+                }
+            }
+        }
+    }
+}
+
 pub trait QueryStrategyImpl: QueryStrategyResult {
     fn execute<'query, E>(
-        executor: E,
+        executor: Result<E, Error>,
         query: String,
         values: Vec<Value<'query>>,
+        log: StatementLog,
     ) -> Self::Result<'query>
     where
         E: AnyExecutor<'query>;
@@ -112,55 +277,147 @@ pub use query_wrapper::QueryWrapper;
 /// Private module to contain the internals behind a sound api
 mod query_wrapper {
     use std::pin::Pin;
+    use std::ptr::NonNull;
+
+    use std::task::Poll;
 
+    use pin_project::{pin_project, pinned_drop};
     use rorm_sql::value::Value;
 
+    use super::StatementLog;
     use crate::internal::any::{AnyExecutor, AnyQuery};
+    use crate::Error;
 
     #[doc(hidden)]
-    #[pin_project::pin_project]
+    #[pin_project(PinnedDrop)]
     pub struct QueryWrapper<T> {
+        // Invariant: `wrapped` has to be declared before `query_string`,
+        //            because it may borrow from it and fields are dropped in declaration order.
         #[pin]
         wrapped: T,
-        #[allow(dead_code)] // is used via a reference inside T
-        query_string: String,
+        query_string: AliasableStr,
+        log: StatementLog,
+    }
+
+    /// Owned string which can be borrowed while being moved around.
+    ///
+    /// Unlike moving a `String`, moving this doesn't assert unique access to the heap allocation.
+    /// Therefore, references into it stay valid until it is dropped.
+    struct AliasableStr(NonNull<str>);
+
+    // SAFETY: `AliasableStr` owns its allocation like a `Box<str>` does
+    unsafe impl Send for AliasableStr {}
+    // SAFETY: `AliasableStr` only hands out shared references
+    unsafe impl Sync for AliasableStr {}
+
+    impl AliasableStr {
+        fn new(string: String) -> Self {
+            Self(NonNull::from(Box::leak(string.into_boxed_str())))
+        }
+
+        /// Borrow the string for an arbitrary lifetime
+        ///
+        /// # Safety
+        /// The caller has to ensure the reference is not used after `self` has been dropped.
+        unsafe fn get<'a>(&self) -> &'a str {
+            self.0.as_ref()
+        }
+    }
+
+    impl Drop for AliasableStr {
+        fn drop(&mut self) {
+            // SAFETY: The pointer was created from a `Box` in `AliasableStr::new`
+            drop(unsafe { Box::from_raw(self.0.as_ptr()) });
+        }
     }
 
     impl<'query, T: 'query> QueryWrapper<T> {
         /// Basic constructor which only performs the unsafe lifetime extension to be tested by miri
-        pub(crate) fn new_basic(string: String, wrapped: impl FnOnce(&'query str) -> T) -> Self {
-            let slice: &str = string.as_str();
+        pub(crate) fn new_basic(
+            string: String,
+            log: StatementLog,
+            wrapped: impl FnOnce(&'query str) -> T,
+        ) -> Self {
+            let query_string = AliasableStr::new(string);
 
-            // SAFETY: The heap allocation won't be dropped or moved
+            // SAFETY: The heap allocation won't be dropped
             //         until `wrapped` which contains this reference is dropped.
-            let slice: &'query str = unsafe { std::mem::transmute(slice) };
+            let slice: &'query str = unsafe { query_string.get() };
 
             Self {
-                query_string: string,
                 wrapped: wrapped(slice),
+                query_string,
+                log,
             }
         }
 
+        /// Start a query and bind its values
+        ///
+        /// `execute` receives an error, if the `executor` is unavailable
+        /// or any of the `values` couldn't be bound.
+        ///
+        /// In debug builds, a warning is logged if the number of `values`
+        /// doesn't match the placeholders in `query_string`.
         pub fn new<'data: 'query>(
-            executor: impl AnyExecutor<'query>,
+            executor: Result<impl AnyExecutor<'query>, Error>,
             query_string: String,
             values: Vec<Value<'data>>,
-            execute: impl FnOnce(AnyQuery<'query>) -> T,
+            log: StatementLog,
+            execute: impl FnOnce(Result<AnyQuery<'query>, Error>) -> T,
         ) -> Self {
-            Self::new_basic(query_string, move |query_string| {
-                let mut query = executor.query(query_string);
-                for value in values {
-                    crate::internal::utils::bind_param(&mut query, value);
+            #[cfg(debug_assertions)]
+            {
+                let expected = crate::executor::count_placeholders(&query_string);
+                if expected != values.len() {
+                    log::warn!(
+                        "The query expects {expected} parameters but {} values are bound: {query_string}",
+                        values.len()
+                    );
                 }
+            }
+
+            Self::new_basic(query_string, log, move |query_string| {
+                let query = executor.and_then(|executor| {
+                    let mut query = executor.query(query_string);
+                    for value in values {
+                        crate::internal::utils::bind_param(&mut query, value)?;
+                    }
+                    Ok(query)
+                });
                 execute(query)
             })
         }
     }
 
     impl<T> QueryWrapper<T> {
-        /// Project a [`Pin`] onto the `wrapped` field
-        pub fn project_wrapped(self: Pin<&mut Self>) -> Pin<&mut T> {
-            self.project().wrapped
+        /// Poll the `wrapped` field while timing the statement
+        ///
+        /// The statement is logged once `is_done` returns `true` for a polled value.
+        pub fn poll_wrapped<R>(
+            self: Pin<&mut Self>,
+            poll: impl FnOnce(Pin<&mut T>) -> Poll<R>,
+            is_done: impl FnOnce(&R) -> bool,
+        ) -> Poll<R> {
+            let this = self.project();
+            this.log.start();
+            let poll = poll(this.wrapped);
+            if let Poll::Ready(value) = &poll {
+                if is_done(value) {
+                    // SAFETY: The reference doesn't outlive this function
+                    this.log.finish(unsafe { this.query_string.get() });
+                }
+            }
+            poll
+        }
+    }
+
+    /// Logs statements which are dropped before they are done
+    #[pinned_drop]
+    impl<T> PinnedDrop for QueryWrapper<T> {
+        fn drop(self: Pin<&mut Self>) {
+            let this = self.project();
+            // SAFETY: The reference doesn't outlive this function
+            this.log.finish(unsafe { this.query_string.get() });
         }
     }
 }
@@ -172,7 +429,7 @@ where
     type Output = F::Output;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        self.project_wrapped().poll(cx)
+        self.poll_wrapped(|wrapped| wrapped.poll(cx), |_| true)
     }
 }
 
@@ -183,27 +440,31 @@ where
     type Item = S::Item;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        self.project_wrapped().poll_next(cx)
+        self.poll_wrapped(|wrapped| wrapped.poll_next(cx), Option::is_none)
     }
 }
 
 impl QueryStrategyResult for Nothing {
     type Result<'query> = QueryFuture<
-        future::MapOk<
-            TryCollect<
-                stream::ErrInto<stream::MapOk<FetchMany<'query>, fn(AnyEither) -> ()>, Error>,
-                Vec<()>,
+        future::Either<
+            Ready<Result<(), Error>>,
+            future::MapOk<
+                TryCollect<
+                    stream::ErrInto<stream::MapOk<FetchMany<'query>, fn(AnyEither) -> ()>, Error>,
+                    Vec<()>,
+                >,
+                fn(Vec<()>) -> (),
             >,
-            fn(Vec<()>) -> (),
         >,
     >;
 }
 
 impl QueryStrategyImpl for Nothing {
     fn execute<'query, E>(
-        executor: E,
+        executor: Result<E, Error>,
         query: String,
         values: Vec<Value<'query>>,
+        log: StatementLog,
     ) -> Self::Result<'query>
     where
         E: AnyExecutor<'query>,
@@ -211,13 +472,16 @@ impl QueryStrategyImpl for Nothing {
         fn dump<T>(_: T) {}
         let dump_either: fn(AnyEither) -> () = dump;
         let dump_vec: fn(Vec<()>) -> () = dump;
-        QueryFuture::new(executor, query, values, |query| {
-            query
-                .fetch_many()
-                .map_ok(dump_either)
-                .err_into()
-                .try_collect()
-                .map_ok(dump_vec)
+        QueryFuture::new(executor, query, values, log, |query| match query {
+            Ok(query) => future::Either::Right(
+                query
+                    .fetch_many()
+                    .map_ok(dump_either)
+                    .err_into()
+                    .try_collect()
+                    .map_ok(dump_vec),
+            ),
+            Err(error) => future::Either::Left(ready(Err(error))),
         })
     }
 }
@@ -228,15 +492,16 @@ impl QueryStrategyResult for AffectedRows {
 
 impl QueryStrategyImpl for AffectedRows {
     fn execute<'query, E>(
-        executor: E,
+        executor: Result<E, Error>,
         query: String,
         values: Vec<Value<'query>>,
+        log: StatementLog,
     ) -> Self::Result<'query>
     where
         E: AnyExecutor<'query>,
     {
-        QueryFuture::new(executor, query, values, |query| {
-            (async move { Ok(query.fetch_affected_rows().await?) }).boxed()
+        QueryFuture::new(executor, query, values, log, |query| {
+            (async move { Ok(query?.fetch_affected_rows().await?) }).boxed()
         })
     }
 }
@@ -247,19 +512,19 @@ impl QueryStrategyResult for One {
 
 impl QueryStrategyImpl for One {
     fn execute<'query, E>(
-        executor: E,
+        executor: Result<E, Error>,
         query: String,
         values: Vec<Value<'query>>,
+        log: StatementLog,
     ) -> Self::Result<'query>
     where
         E: AnyExecutor<'query>,
     {
-        QueryFuture::new(executor, query, values, |query| {
+        QueryFuture::new(executor, query, values, log, |query| {
             (async move {
-                Ok(Row(query
-                    .fetch_optional()
-                    .await?
-                    .ok_or(sqlx::Error::RowNotFound)?))
+                let query = query?;
+                let not_found = not_found(&query);
+                query.fetch_optional().await?.map(Row).ok_or(not_found)
             })
             .boxed()
         })
@@ -272,15 +537,16 @@ impl QueryStrategyResult for Optional {
 
 impl QueryStrategyImpl for Optional {
     fn execute<'query, E>(
-        executor: E,
+        executor: Result<E, Error>,
         query: String,
         values: Vec<Value<'query>>,
+        log: StatementLog,
     ) -> Self::Result<'query>
     where
         E: AnyExecutor<'query>,
     {
-        QueryFuture::new(executor, query, values, |query| {
-            (async move { Ok(query.fetch_optional().await?.map(Row)) }).boxed()
+        QueryFuture::new(executor, query, values, log, |query| {
+            (async move { Ok(query?.fetch_optional().await?.map(Row)) }).boxed()
         })
     }
 }
@@ -296,56 +562,198 @@ static TRY_FILTER_MAP: fn(AnyEither) -> Ready<Result<Option<Row>, sqlx::Error>>
     convert
 };
 
+impl QueryStrategyResult for ExactlyOne {
+    type Result<'query> = QueryFuture<BoxFuture<'query, Result<Row, Error>>>;
+}
+
+impl QueryStrategyImpl for ExactlyOne {
+    fn execute<'query, E>(
+        executor: Result<E, Error>,
+        query: String,
+        values: Vec<Value<'query>>,
+        log: StatementLog,
+    ) -> Self::Result<'query>
+    where
+        E: AnyExecutor<'query>,
+    {
+        QueryFuture::new(executor, query, values, log, |query| {
+            (async move {
+                let query = query?;
+                let not_found = not_found(&query);
+                fetch_at_most_one(query).await?.ok_or(not_found)
+            })
+            .boxed()
+        })
+    }
+}
+
+impl QueryStrategyResult for AtMostOne {
+    type Result<'query> = QueryFuture<BoxFuture<'query, Result<Option<Row>, Error>>>;
+}
+
+impl QueryStrategyImpl for AtMostOne {
+    fn execute<'query, E>(
+        executor: Result<E, Error>,
+        query: String,
+        values: Vec<Value<'query>>,
+        log: StatementLog,
+    ) -> Self::Result<'query>
+    where
+        E: AnyExecutor<'query>,
+    {
+        QueryFuture::new(executor, query, values, log, |query| {
+            (async move { fetch_at_most_one(query?).await }).boxed()
+        })
+    }
+}
+
+/// The error of a strategy requiring a row if `query` doesn't return one
+///
+/// A dry run never returns a row, which is reported as [`Error::DryRun`] instead.
+fn not_found(query: &AnyQuery<'_>) -> Error {
+    if query.is_dry_run() {
+        Error::DryRun
+    } else {
+        Error::RowNotFound
+    }
+}
+
+/// Fetch the first row and fail with [`Error::TooManyRows`] if there is a second one
+async fn fetch_at_most_one(query: AnyQuery<'_>) -> Result<Option<Row>, Error> {
+    let mut rows = query.fetch_many().try_filter_map(TRY_FILTER_MAP);
+    let first = rows.try_next().await?;
+    if first.is_some() && rows.try_next().await?.is_some() {
+        return Err(Error::TooManyRows);
+    }
+    Ok(first)
+}
+
 impl QueryStrategyResult for All {
     type Result<'query> = QueryFuture<BoxFuture<'query, Result<Vec<Row>, Error>>>;
 }
 
 impl QueryStrategyImpl for All {
     fn execute<'query, E>(
-        executor: E,
+        executor: Result<E, Error>,
+        query: String,
+        values: Vec<Value<'query>>,
+        log: StatementLog,
+    ) -> Self::Result<'query>
+    where
+        E: AnyExecutor<'query>,
+    {
+        QueryFuture::new(executor, query, values, log, |query| {
+            (async move { Ok(query?.fetch_all().await?.into_iter().map(Row).collect()) }).boxed()
+        })
+    }
+}
+
+impl QueryStrategyResult for AllWithAffected {
+    type Result<'query> = QueryFuture<BoxFuture<'query, Result<(Vec<Row>, u64), Error>>>;
+}
+
+impl QueryStrategyImpl for AllWithAffected {
+    fn execute<'query, E>(
+        executor: Result<E, Error>,
+        query: String,
+        values: Vec<Value<'query>>,
+        log: StatementLog,
+    ) -> Self::Result<'query>
+    where
+        E: AnyExecutor<'query>,
+    {
+        QueryFuture::new(executor, query, values, log, |query| {
+            (async move {
+                let mut rows = Vec::new();
+                let mut affected = 0;
+                let mut stream = query?.fetch_many();
+                while let Some(either) = stream.try_next().await? {
+                    match either {
+                        AnyEither::Left(result) => affected += result.rows_affected(),
+                        AnyEither::Right(row) => rows.push(Row(row)),
+                    }
+                }
+                Ok((rows, affected))
+            })
+            .boxed()
+        })
+    }
+}
+
+impl QueryStrategyResult for MultiResultSet {
+    type Result<'query> = QueryFuture<BoxFuture<'query, Result<Vec<Vec<Row>>, Error>>>;
+}
+
+impl QueryStrategyImpl for MultiResultSet {
+    fn execute<'query, E>(
+        executor: Result<E, Error>,
         query: String,
         values: Vec<Value<'query>>,
+        log: StatementLog,
     ) -> Self::Result<'query>
     where
         E: AnyExecutor<'query>,
     {
-        QueryFuture::new(executor, query, values, |query| {
-            (async move { Ok(query.fetch_all().await?.into_iter().map(Row).collect()) }).boxed()
+        QueryFuture::new(executor, query, values, log, |query| {
+            (async move {
+                let mut result_sets = Vec::new();
+                let mut rows = Vec::new();
+                let mut stream = query?.fetch_many();
+                while let Some(either) = stream.try_next().await? {
+                    match either {
+                        // The query result marks the end of a result set
+                        AnyEither::Left(_) => result_sets.push(std::mem::take(&mut rows)),
+                        AnyEither::Right(row) => rows.push(Row(row)),
+                    }
+                }
+                if !rows.is_empty() {
+                    result_sets.push(rows);
+                }
+                Ok(result_sets)
+            })
+            .boxed()
         })
     }
 }
 
 impl QueryStrategyResult for Stream {
     type Result<'query> = QueryStream<
-        stream::ErrInto<
-            TryFilterMap<
-                FetchMany<'query>,
-                Ready<Result<Option<Row>, sqlx::Error>>,
-                fn(AnyEither) -> Ready<Result<Option<Row>, sqlx::Error>>,
+        future::Either<
+            stream::Once<Ready<Result<Row, Error>>>,
+            stream::ErrInto<
+                TryFilterMap<
+                    FetchMany<'query>,
+                    Ready<Result<Option<Row>, sqlx::Error>>,
+                    fn(AnyEither) -> Ready<Result<Option<Row>, sqlx::Error>>,
+                >,
+                Error,
             >,
-            Error,
         >,
     >;
 }
 
 impl QueryStrategyImpl for Stream {
     fn execute<'query, E>(
-        executor: E,
+        executor: Result<E, Error>,
         query: String,
         values: Vec<Value<'query>>,
+        log: StatementLog,
     ) -> Self::Result<'query>
     where
         E: AnyExecutor<'query>,
     {
-        QueryStream::new(executor, query, values, |query| {
-            query.fetch_many().try_filter_map(TRY_FILTER_MAP).err_into()
+        QueryStream::new(executor, query, values, log, |query| match query {
+            Ok(query) => {
+                future::Either::Right(query.fetch_many().try_filter_map(TRY_FILTER_MAP).err_into())
+            }
+            Err(error) => future::Either::Left(stream::once(ready(Err(error)))),
         })
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::internal::executor::QueryWrapper;
+    use crate::internal::executor::{QueryWrapper, StatementLog};
 
     /// Run this test with miri
     ///
@@ -361,6 +769,7 @@ mod test {
                 println!("{}", self.0);
             }
         }
-        let _w = QueryWrapper::new_basic(format!("Hello World"), BorrowStr);
+        let _w =
+            QueryWrapper::new_basic(format!("Hello World"), StatementLog::disabled(), BorrowStr);
     }
 }
@@ -6,8 +6,66 @@
 compile_error!("Can't compile with sqlx without any database");
 
 pub(crate) mod any;
+pub(crate) mod connection;
 pub(crate) mod database;
 pub(crate) mod executor;
 pub(crate) mod row;
 pub(crate) mod transaction;
 pub(crate) mod utils;
+
+/// Connects to a new in-memory SQLite database for tests
+///
+/// The pool is limited to a single connection, because every connection opens its own in-memory database.
+#[cfg(all(test, feature = "sqlite", feature = "tokio"))]
+pub(crate) async fn connect_sqlite() -> crate::Database {
+    let mut configuration =
+        crate::DatabaseConfiguration::new(rorm_declaration::config::DatabaseDriver::SQLite {
+            filename: String::from(":memory:"),
+        });
+    configuration.max_connections = 1;
+    crate::Database::connect(configuration).await.unwrap()
+}
+
+/// Reads an environment variable configuring the test databases, falling back to `default`
+#[cfg(all(test, any(feature = "postgres", feature = "mysql"), feature = "tokio"))]
+fn env_or(name: &str, default: &str) -> String {
+    std::env::var(name).unwrap_or_else(|_| String::from(default))
+}
+
+/// Connects to the postgres database used by tests which are run using `cargo test -- --ignored`
+///
+/// It connects to the database `rorm` on localhost as user `rorm` with password `rorm`,
+/// which can be overwritten using the environment variables
+/// `RORM_TEST_HOST`, `RORM_TEST_NAME`, `RORM_TEST_USER` and `RORM_TEST_PASSWORD`.
+///
+/// The pool is limited to a single connection, because temporary tables only exist on the connection which created them.
+#[cfg(all(test, feature = "postgres", feature = "tokio"))]
+pub(crate) async fn connect_postgres() -> crate::Database {
+    let mut configuration =
+        crate::DatabaseConfiguration::new(rorm_declaration::config::DatabaseDriver::Postgres {
+            host: env_or("RORM_TEST_HOST", "localhost"),
+            port: 5432,
+            name: env_or("RORM_TEST_NAME", "rorm"),
+            user: env_or("RORM_TEST_USER", "rorm"),
+            password: env_or("RORM_TEST_PASSWORD", "rorm"),
+        });
+    configuration.max_connections = 1;
+    crate::Database::connect(configuration).await.unwrap()
+}
+
+/// Connects to the mysql database used by tests which are run using `cargo test -- --ignored`
+///
+/// It uses the same environment variables as `connect_postgres` but the port 3306.
+#[cfg(all(test, feature = "mysql", feature = "tokio"))]
+pub(crate) async fn connect_mysql() -> crate::Database {
+    let mut configuration =
+        crate::DatabaseConfiguration::new(rorm_declaration::config::DatabaseDriver::MySQL {
+            host: env_or("RORM_TEST_HOST", "localhost"),
+            port: 3306,
+            name: env_or("RORM_TEST_NAME", "rorm"),
+            user: env_or("RORM_TEST_USER", "rorm"),
+            password: env_or("RORM_TEST_PASSWORD", "rorm"),
+        });
+    configuration.max_connections = 1;
+    crate::Database::connect(configuration).await.unwrap()
+}
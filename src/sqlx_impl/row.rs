@@ -1,7 +1,10 @@
-use sqlx::Row as SqlxRowTrait;
+use std::collections::HashMap;
+
+use sqlx::types::Json;
+use sqlx::{Column, Row as SqlxRowTrait, TypeInfo, ValueRef};
 
 use crate::internal::any::AnyRow;
-use crate::row::{Decode, RowIndex};
+use crate::row::{Decode, OwnedValue, RowIndex};
 use crate::{Error, Row};
 
 pub(crate) type Impl = AnyRow;
@@ -20,5 +23,502 @@ where
         #[cfg(feature = "sqlite")]
         AnyRow::Sqlite(row) => row.try_get(index),
     };
+    result.map_err(|error| match error {
+        sqlx::Error::ColumnDecode { index, source } => Error::DecodeError(format!(
+            "could not decode column {index} as {}: {source}",
+            std::any::type_name::<T>()
+        )),
+        error => Error::SqlxError(error),
+    })
+}
+
+/// Implementation of [Row::get_raw]
+pub(crate) fn get_raw<I>(row: &Row, index: I) -> Result<&[u8], Error>
+where
+    I: RowIndex,
+{
+    let result = match &row.0 {
+        #[cfg(feature = "postgres")]
+        AnyRow::Postgres(row) => row.try_get_unchecked::<&[u8], _>(index),
+        #[cfg(feature = "mysql")]
+        AnyRow::MySql(row) => row.try_get_unchecked::<&[u8], _>(index),
+        #[cfg(feature = "sqlite")]
+        AnyRow::Sqlite(row) => row.try_get_unchecked::<&[u8], _>(index),
+    };
     result.map_err(Error::SqlxError)
 }
+
+/// Implementation of [Row::get_numeric_str]
+pub(crate) fn get_numeric_str<I>(row: &Row, index: I) -> Result<String, Error>
+where
+    I: RowIndex,
+{
+    let result = match &row.0 {
+        #[cfg(feature = "postgres")]
+        AnyRow::Postgres(row) => get_numeric_str_postgres(row, index)?,
+        #[cfg(feature = "mysql")]
+        AnyRow::MySql(row) => std::str::from_utf8(row.try_get_unchecked(index)?)
+            .map(str::to_string)
+            .map_err(Into::into),
+        #[cfg(feature = "sqlite")]
+        AnyRow::Sqlite(row) => std::str::from_utf8(row.try_get_unchecked(index)?)
+            .map(str::to_string)
+            .map_err(Into::into),
+    };
+    result.map_err(|error| Error::DecodeError(format!("could not decode numeric: {error}")))
+}
+
+#[cfg(feature = "postgres")]
+fn get_numeric_str_postgres<I>(
+    row: &sqlx::postgres::PgRow,
+    index: I,
+) -> Result<Result<String, sqlx::error::BoxDynError>, sqlx::Error>
+where
+    I: RowIndex,
+{
+    use sqlx::postgres::PgValueFormat;
+
+    let value = row.try_get_raw(index)?;
+    if value.is_null() {
+        return Ok(Err("unexpected NULL".into()));
+    }
+    Ok(match value.format() {
+        PgValueFormat::Text => value.as_str().map(str::to_string),
+        PgValueFormat::Binary => value
+            .as_bytes()
+            .and_then(|bytes| decode_pg_numeric(bytes).map_err(Into::into)),
+    })
+}
+
+/// Convert postgres' binary `NUMERIC` format into its textual representation
+///
+/// The binary format consists of big endian `u16`s:
+/// the number of digits, the weight of the first digit, the sign, the display scale
+/// followed by the digits themselves in base 10000.
+#[cfg(feature = "postgres")]
+fn decode_pg_numeric(bytes: &[u8]) -> Result<String, String> {
+    use std::fmt::Write;
+
+    let read = |position: usize| {
+        bytes
+            .get(position * 2..position * 2 + 2)
+            .map(|word| u16::from_be_bytes([word[0], word[1]]))
+            .ok_or_else(|| String::from("unexpected end of input"))
+    };
+
+    let num_digits = read(0)? as usize;
+    let weight = read(1)? as i16 as isize;
+    let sign = read(2)?;
+    let scale = read(3)? as usize;
+    let digits = (0..num_digits)
+        .map(|position| read(4 + position))
+        .collect::<Result<Vec<_>, _>>()?;
+    let digit = |position: isize| {
+        usize::try_from(position)
+            .ok()
+            .and_then(|position| digits.get(position).copied())
+            .unwrap_or(0)
+    };
+
+    let mut string = match sign {
+        0x0000 => String::new(),
+        0x4000 => String::from("-"),
+        0xC000 => return Ok(String::from("NaN")),
+        0xD000 => return Ok(String::from("Infinity")),
+        0xF000 => return Ok(String::from("-Infinity")),
+        _ => return Err(format!("invalid sign {sign:#x}")),
+    };
+
+    if weight < 0 {
+        string.push('0');
+    } else {
+        let _ = write!(string, "{}", digit(0));
+        for position in 1..=weight {
+            let _ = write!(string, "{:04}", digit(position));
+        }
+    }
+
+    if scale > 0 {
+        let mut fraction = String::with_capacity(scale + 4);
+        let mut position = weight + 1;
+        while fraction.len() < scale {
+            let _ = write!(fraction, "{:04}", digit(position));
+            position += 1;
+        }
+        fraction.truncate(scale);
+        string.push('.');
+        string.push_str(&fraction);
+    }
+
+    Ok(string)
+}
+
+/// Implementation of [Row::len]
+pub(crate) fn len(row: &Row) -> usize {
+    match &row.0 {
+        #[cfg(feature = "postgres")]
+        AnyRow::Postgres(row) => row.len(),
+        #[cfg(feature = "mysql")]
+        AnyRow::MySql(row) => row.len(),
+        #[cfg(feature = "sqlite")]
+        AnyRow::Sqlite(row) => row.len(),
+    }
+}
+
+/// Implementation of [Row::column_names]
+pub(crate) fn column_names(row: &Row) -> Vec<&str> {
+    match &row.0 {
+        #[cfg(feature = "postgres")]
+        AnyRow::Postgres(row) => row.columns().iter().map(Column::name).collect(),
+        #[cfg(feature = "mysql")]
+        AnyRow::MySql(row) => row.columns().iter().map(Column::name).collect(),
+        #[cfg(feature = "sqlite")]
+        AnyRow::Sqlite(row) => row.columns().iter().map(Column::name).collect(),
+    }
+}
+
+/// Implementation of [Row::into_map]
+pub(crate) fn into_map(row: Row) -> Result<HashMap<String, OwnedValue>, Error> {
+    column_names(&row)
+        .into_iter()
+        .enumerate()
+        .map(|(index, name)| Ok((name.to_string(), get_owned(&row, index)?)))
+        .collect()
+}
+
+/// Implementation of [Row::into_values]
+pub(crate) fn into_values(row: Row) -> Result<Vec<OwnedValue>, Error> {
+    (0..column_names(&row).len())
+        .map(|index| get_owned(&row, index))
+        .collect()
+}
+
+/// Decode a cell into an [`OwnedValue`] chosen by its type
+pub(crate) fn get_owned(row: &Row, index: usize) -> Result<OwnedValue, Error> {
+    let result = match &row.0 {
+        #[cfg(feature = "postgres")]
+        AnyRow::Postgres(row) => get_owned_postgres(row, index),
+        #[cfg(feature = "mysql")]
+        AnyRow::MySql(row) => get_owned_mysql(row, index),
+        #[cfg(feature = "sqlite")]
+        AnyRow::Sqlite(row) => get_owned_sqlite(row, index),
+    };
+    result.map_err(|error| Error::DecodeError(format!("could not decode column {index}: {error}")))
+}
+
+/// Implementation of [Row::get_as_string]
+pub(crate) fn get_as_string(row: &Row, index: usize) -> Result<String, Error> {
+    let result = match &row.0 {
+        #[cfg(feature = "postgres")]
+        AnyRow::Postgres(row) => get_as_string_postgres(row, index),
+        #[cfg(feature = "mysql")]
+        AnyRow::MySql(row) => get_owned_mysql(row, index).map(owned_to_string),
+        #[cfg(feature = "sqlite")]
+        AnyRow::Sqlite(row) => get_owned_sqlite(row, index).map(owned_to_string),
+    };
+    match result {
+        Ok(Some(string)) => Ok(string),
+        Ok(None) => Err(Error::DecodeError(format!(
+            "could not decode column {index} as string: unexpected NULL"
+        ))),
+        Err(error) => Err(Error::DecodeError(format!(
+            "could not decode column {index} as string: {error}"
+        ))),
+    }
+}
+
+#[cfg(feature = "postgres")]
+fn get_as_string_postgres(
+    row: &sqlx::postgres::PgRow,
+    index: usize,
+) -> Result<Option<String>, sqlx::Error> {
+    use sqlx::postgres::PgValueFormat;
+
+    let value = row.try_get_raw(index)?;
+    if value.is_null() {
+        return Ok(None);
+    }
+    let string = match value.format() {
+        PgValueFormat::Text => value.as_str().map(str::to_string),
+        PgValueFormat::Binary if value.type_info().name() == "NUMERIC" => value
+            .as_bytes()
+            .and_then(|bytes| decode_pg_numeric(bytes).map_err(Into::into)),
+        PgValueFormat::Binary => return get_owned_postgres(row, index).map(owned_to_string),
+    };
+    string.map(Some).map_err(sqlx::Error::Decode)
+}
+
+/// Render an [`OwnedValue`] for [Row::get_as_string]
+fn owned_to_string(value: OwnedValue) -> Option<String> {
+    use std::fmt::Write;
+
+    Some(match value {
+        OwnedValue::Null => return None,
+        OwnedValue::Bool(value) => value.to_string(),
+        OwnedValue::I16(value) => value.to_string(),
+        OwnedValue::I32(value) => value.to_string(),
+        OwnedValue::I64(value) => value.to_string(),
+        OwnedValue::F32(value) => value.to_string(),
+        OwnedValue::F64(value) => value.to_string(),
+        OwnedValue::String(value) => value,
+        OwnedValue::Binary(value) => {
+            let mut string = String::with_capacity(2 + 2 * value.len());
+            string.push_str("\\x");
+            for byte in value {
+                let _ = write!(string, "{byte:02x}");
+            }
+            string
+        }
+        OwnedValue::ChronoNaiveDate(value) => value.to_string(),
+        OwnedValue::ChronoNaiveTime(value) => value.to_string(),
+        OwnedValue::ChronoNaiveDateTime(value) => value.to_string(),
+        OwnedValue::ChronoDateTime(value) => value.to_string(),
+        OwnedValue::Uuid(value) => value.to_string(),
+        OwnedValue::JsonValue(value) => value.to_string(),
+    })
+}
+
+#[cfg(feature = "postgres")]
+fn get_owned_postgres(
+    row: &sqlx::postgres::PgRow,
+    index: usize,
+) -> Result<OwnedValue, sqlx::Error> {
+    let value = row.try_get_raw(index)?;
+    if value.is_null() {
+        return Ok(OwnedValue::Null);
+    }
+    Ok(match value.type_info().name() {
+        "BOOL" => OwnedValue::Bool(row.try_get(index)?),
+        "INT2" => OwnedValue::I16(row.try_get(index)?),
+        "INT4" => OwnedValue::I32(row.try_get(index)?),
+        "INT8" => OwnedValue::I64(row.try_get(index)?),
+        "FLOAT4" => OwnedValue::F32(row.try_get(index)?),
+        "FLOAT8" => OwnedValue::F64(row.try_get(index)?),
+        "BYTEA" => OwnedValue::Binary(row.try_get(index)?),
+        "DATE" => OwnedValue::ChronoNaiveDate(row.try_get(index)?),
+        "TIME" => OwnedValue::ChronoNaiveTime(row.try_get(index)?),
+        "TIMESTAMP" => OwnedValue::ChronoNaiveDateTime(row.try_get(index)?),
+        "TIMESTAMPTZ" => OwnedValue::ChronoDateTime(row.try_get(index)?),
+        "UUID" => OwnedValue::Uuid(row.try_get(index)?),
+        "JSON" | "JSONB" => OwnedValue::JsonValue(row.try_get::<Json<_>, _>(index)?.0),
+        _ => OwnedValue::String(row.try_get_unchecked(index)?),
+    })
+}
+
+#[cfg(feature = "mysql")]
+fn get_owned_mysql(row: &sqlx::mysql::MySqlRow, index: usize) -> Result<OwnedValue, sqlx::Error> {
+    let value = row.try_get_raw(index)?;
+    if value.is_null() {
+        return Ok(OwnedValue::Null);
+    }
+    Ok(match value.type_info().name() {
+        "BOOLEAN" => OwnedValue::Bool(row.try_get(index)?),
+        "TINYINT" | "SMALLINT" | "MEDIUMINT" | "INT" | "BIGINT" | "YEAR" => {
+            OwnedValue::I64(row.try_get_unchecked(index)?)
+        }
+        "TINYINT UNSIGNED" | "SMALLINT UNSIGNED" | "MEDIUMINT UNSIGNED" | "INT UNSIGNED"
+        | "BIGINT UNSIGNED" => {
+            let value: u64 = row.try_get_unchecked(index)?;
+            OwnedValue::I64(
+                i64::try_from(value).map_err(|error| sqlx::Error::Decode(error.into()))?,
+            )
+        }
+        "FLOAT" => OwnedValue::F32(row.try_get(index)?),
+        "DOUBLE" => OwnedValue::F64(row.try_get(index)?),
+        "BINARY" | "VARBINARY" | "TINYBLOB" | "BLOB" | "MEDIUMBLOB" | "LONGBLOB" => {
+            OwnedValue::Binary(row.try_get_unchecked(index)?)
+        }
+        "DATE" => OwnedValue::ChronoNaiveDate(row.try_get(index)?),
+        "TIME" => OwnedValue::ChronoNaiveTime(row.try_get(index)?),
+        "DATETIME" => OwnedValue::ChronoNaiveDateTime(row.try_get(index)?),
+        "TIMESTAMP" => OwnedValue::ChronoDateTime(row.try_get(index)?),
+        "JSON" => OwnedValue::JsonValue(row.try_get::<Json<_>, _>(index)?.0),
+        _ => OwnedValue::String(row.try_get_unchecked(index)?),
+    })
+}
+
+#[cfg(feature = "sqlite")]
+fn get_owned_sqlite(
+    row: &sqlx::sqlite::SqliteRow,
+    index: usize,
+) -> Result<OwnedValue, sqlx::Error> {
+    let value = row.try_get_raw(index)?;
+    if value.is_null() {
+        return Ok(OwnedValue::Null);
+    }
+    // SQLite's values only carry their storage class
+    Ok(match value.type_info().name() {
+        "INTEGER" => OwnedValue::I64(row.try_get_unchecked(index)?),
+        "REAL" => OwnedValue::F64(row.try_get_unchecked(index)?),
+        "BLOB" => OwnedValue::Binary(row.try_get_unchecked(index)?),
+        _ => OwnedValue::String(row.try_get_unchecked(index)?),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn test_decode_pg_numeric() {
+        use super::decode_pg_numeric;
+
+        fn encode(words: &[u16]) -> Vec<u8> {
+            words.iter().flat_map(|word| word.to_be_bytes()).collect()
+        }
+
+        // 12345.678
+        let bytes = encode(&[3, 1, 0x0000, 3, 1, 2345, 6780]);
+        assert_eq!(decode_pg_numeric(&bytes).unwrap(), "12345.678");
+
+        // -0.0001
+        let bytes = encode(&[1, (-1i16) as u16, 0x4000, 4, 1]);
+        assert_eq!(decode_pg_numeric(&bytes).unwrap(), "-0.0001");
+
+        // 100000000.00
+        let bytes = encode(&[1, 2, 0x0000, 2, 1]);
+        assert_eq!(decode_pg_numeric(&bytes).unwrap(), "100000000.00");
+
+        let bytes = encode(&[0, 0, 0xC000, 0]);
+        assert_eq!(decode_pg_numeric(&bytes).unwrap(), "NaN");
+    }
+
+    /// Run `query` and return its single row
+    #[cfg(feature = "tokio")]
+    async fn select_one(db: &crate::Database, query: &str) -> crate::Row {
+        use crate::executor::{Executor, One};
+
+        db.execute::<One>(query.to_string(), Vec::new())
+            .await
+            .unwrap()
+    }
+
+    /// Decode dates and times stored in the columns each dialect uses for them
+    #[cfg(feature = "tokio")]
+    async fn test_decode_date_time(db: crate::Database) {
+        use rorm_sql::DBImpl;
+        use sqlx::types::chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+        use sqlx::types::time::{Date, PrimitiveDateTime, Time};
+
+        use crate::executor::{Executor, Nothing};
+
+        // Column types for `date_only`, `time_only`, `date_time` and `utc`
+        // as well as the literal stored in `utc`
+        let (column_types, utc) = match db.dialect() {
+            #[cfg(feature = "sqlite")]
+            DBImpl::SQLite => (["TEXT"; 4], "2024-02-29 13:37:00+00:00"),
+            #[cfg(feature = "postgres")]
+            DBImpl::Postgres => (
+                ["DATE", "TIME", "TIMESTAMP", "TIMESTAMPTZ"],
+                "2024-02-29 13:37:00+00:00",
+            ),
+            // The connection's time_zone is set to UTC
+            #[cfg(feature = "mysql")]
+            DBImpl::MySQL => (
+                ["DATE", "TIME", "DATETIME", "TIMESTAMP"],
+                "2024-02-29 13:37:00",
+            ),
+        };
+        let [date_type, time_type, date_time_type, utc_type] = column_types;
+
+        db.execute::<Nothing>(
+            format!(
+                "CREATE TEMPORARY TABLE decode_date_time (date_only {date_type}, \
+                time_only {time_type}, date_time {date_time_type}, utc {utc_type});"
+            ),
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+        db.execute::<Nothing>(
+            format!(
+                "INSERT INTO decode_date_time (date_only, time_only, date_time, utc) \
+                VALUES ('2024-02-29', '13:37:00', '2024-02-29 13:37:00', '{utc}');"
+            ),
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+        let row = select_one(&db, "SELECT * FROM decode_date_time;").await;
+        db.execute::<Nothing>(String::from("DROP TABLE decode_date_time;"), Vec::new())
+            .await
+            .unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        let time = NaiveTime::from_hms_opt(13, 37, 0).unwrap();
+        assert_eq!(row.get::<NaiveDate, _>("date_only").unwrap(), date);
+        assert_eq!(row.get::<NaiveTime, _>("time_only").unwrap(), time);
+        assert_eq!(
+            row.get::<NaiveDateTime, _>("date_time").unwrap(),
+            NaiveDateTime::new(date, time)
+        );
+        assert_eq!(
+            row.get::<DateTime<Utc>, _>("utc").unwrap(),
+            NaiveDateTime::new(date, time).and_utc()
+        );
+
+        let date = Date::from_ordinal_date(2024, 60).unwrap();
+        let time = Time::from_hms(13, 37, 0).unwrap();
+        assert_eq!(row.get::<Date, _>("date_only").unwrap(), date);
+        assert_eq!(row.get::<Time, _>("time_only").unwrap(), time);
+        assert_eq!(
+            row.get::<PrimitiveDateTime, _>("date_time").unwrap(),
+            PrimitiveDateTime::new(date, time)
+        );
+
+        let error = row.get::<NaiveDate, _>("time_only").unwrap_err();
+        assert!(error.to_string().contains("NaiveDate"), "{error}");
+
+        drop(row);
+        db.close().await;
+    }
+
+    #[cfg(all(feature = "sqlite", feature = "tokio"))]
+    #[tokio::test]
+    async fn test_decode_date_time_sqlite() {
+        test_decode_date_time(crate::internal::connect_sqlite().await).await;
+    }
+
+    #[cfg(all(feature = "postgres", feature = "tokio"))]
+    #[tokio::test]
+    #[ignore = "requires a postgres server"]
+    async fn test_decode_date_time_postgres() {
+        test_decode_date_time(crate::internal::connect_postgres().await).await;
+    }
+
+    #[cfg(all(feature = "mysql", feature = "tokio"))]
+    #[tokio::test]
+    #[ignore = "requires a mysql server"]
+    async fn test_decode_date_time_mysql() {
+        test_decode_date_time(crate::internal::connect_mysql().await).await;
+    }
+
+    #[cfg(all(feature = "sqlite", feature = "tokio"))]
+    #[tokio::test]
+    async fn test_decode_cow_str_sqlite() {
+        use std::borrow::Cow;
+
+        let db = crate::internal::connect_sqlite().await;
+        let row = select_one(&db, "SELECT 'hello' AS text, NULL AS missing;").await;
+
+        let text = row.get::<Cow<str>, _>("text").unwrap();
+        assert!(matches!(text, Cow::Borrowed("hello")), "{text:?}");
+        assert_eq!(row.get_optional::<Cow<str>, _>("missing").unwrap(), None);
+
+        let owned: String = text.into_owned();
+        drop(row);
+        assert_eq!(owned, "hello");
+    }
+
+    #[cfg(all(feature = "sqlite", feature = "tokio"))]
+    #[tokio::test]
+    async fn test_get_as_string_sqlite() {
+        let db = crate::internal::connect_sqlite().await;
+        let row = select_one(&db, "SELECT 42, 1.5, 'foo', X'00ff', NULL;").await;
+
+        assert_eq!(row.get_as_string(0).unwrap(), "42");
+        assert_eq!(row.get_as_string(1).unwrap(), "1.5");
+        assert_eq!(row.get_as_string(2).unwrap(), "foo");
+        assert_eq!(row.get_as_string(3).unwrap(), "\\x00ff");
+        assert!(row.get_as_string(4).is_err());
+    }
+}
@@ -1,9 +1,23 @@
+use rorm_sql::DBImpl;
+
 use crate::internal::any::AnyTransaction;
 use crate::transaction::Transaction;
 use crate::Error;
 
 pub(crate) type Impl = AnyTransaction;
 
+/// Implementation of [Executor::dialect](crate::executor::Executor::dialect) for a shared transaction
+pub(crate) fn dialect(transaction: &Transaction) -> DBImpl {
+    match transaction.0 {
+        #[cfg(feature = "postgres")]
+        AnyTransaction::Postgres(_) => DBImpl::Postgres,
+        #[cfg(feature = "mysql")]
+        AnyTransaction::MySql(_) => DBImpl::MySQL,
+        #[cfg(feature = "sqlite")]
+        AnyTransaction::Sqlite(_) => DBImpl::SQLite,
+    }
+}
+
 /// Implementation of [Transaction::commit]
 pub(crate) async fn commit(transaction: Transaction) -> Result<(), Error> {
     transaction.0.commit().await.map_err(Error::SqlxError)
@@ -13,3 +27,31 @@ pub(crate) async fn commit(transaction: Transaction) -> Result<(), Error> {
 pub(crate) async fn rollback(transaction: Transaction) -> Result<(), Error> {
     transaction.0.rollback().await.map_err(Error::SqlxError)
 }
+
+/// Implementation of [Transaction::start_transaction]
+pub(crate) async fn begin_savepoint(transaction: &mut Transaction) -> Result<(), Error> {
+    transaction.0.begin_nested().await.map_err(Error::SqlxError)
+}
+
+/// Implementation of [Savepoint::commit](crate::transaction::Savepoint::commit)
+pub(crate) async fn commit_savepoint(transaction: &mut Transaction) -> Result<(), Error> {
+    transaction
+        .0
+        .commit_nested()
+        .await
+        .map_err(Error::SqlxError)
+}
+
+/// Implementation of [Savepoint::rollback](crate::transaction::Savepoint::rollback)
+pub(crate) async fn rollback_savepoint(transaction: &mut Transaction) -> Result<(), Error> {
+    transaction
+        .0
+        .rollback_nested()
+        .await
+        .map_err(Error::SqlxError)
+}
+
+/// Implementation of [Savepoint::drop](crate::transaction::Savepoint)
+pub(crate) fn start_rollback_savepoint(transaction: &mut Transaction) {
+    transaction.0.start_rollback_nested()
+}
@@ -1,12 +1,37 @@
 //! Utility functions
 
 use rorm_sql::value::{NullType, Value};
+use sqlx::types::time::UtcOffset;
 use sqlx::types::Json;
 
 use super::any::{AnyEncode, AnyQuery, AnyType};
+use crate::choice::Choice;
+use crate::error::Error;
 
 /// This helper method is used to bind ConditionValues to the query.
-pub fn bind_param<'post_query, 'query>(query: &mut AnyQuery<'query>, param: Value<'post_query>)
+///
+/// Values which can't be bound as parameter (identifiers and columns)
+/// produce an [`Error::BindError`] instead of silently desyncing the placeholders.
+///
+/// # Date and time
+/// Values without timezone (`ChronoNaiveDateTime`, `TimePrimitiveDateTime`)
+/// are bound as `timestamp` in postgres, `DATETIME` in mysql and as text without offset in sqlite.
+///
+/// Values with timezone (`ChronoDateTime`, `TimeOffsetDateTime`)
+/// are normalized to UTC and bound as `timestamptz` in postgres,
+/// as UTC `DATETIME` in mysql and as text with a `+00:00` offset in sqlite.
+///
+/// Postgres converts between `timestamp` and `timestamptz` using the session's `TimeZone`,
+/// so always bind values of the kind matching the column's type.
+///
+/// # Borrowing
+/// Large payloads (`String`, `Binary`, `JsonValue`, ...) are bound by reference
+/// and sqlx encodes them once into its argument buffer without an intermediate copy.
+/// Only choices and the uuid variants which are bound as text allocate their string.
+pub fn bind_param<'post_query, 'query>(
+    query: &mut AnyQuery<'query>,
+    param: Value<'post_query>,
+) -> Result<(), Error>
 where
     'post_query: 'query,
 {
@@ -19,9 +44,17 @@ where
         Value::F32(x) => query.bind(x),
         Value::F64(x) => query.bind(x),
         Value::Binary(x) => query.bind(x),
-        Value::Ident(_) => {}
-        Value::Column { .. } => {}
-        Value::Choice(_) => {}
+        Value::Ident(ident) => {
+            return Err(Error::BindError(format!(
+                "the identifier `{ident}` can't be bound as parameter"
+            )))
+        }
+        Value::Column { .. } => {
+            return Err(Error::BindError(String::from(
+                "a column reference can't be bound as parameter",
+            )))
+        }
+        Value::Choice(x) => query.bind(Choice(x.to_string())),
 
         Value::ChronoNaiveDate(x) => query.bind(x),
         Value::ChronoNaiveTime(x) => query.bind(x),
@@ -30,7 +63,7 @@ where
 
         Value::TimeDate(x) => query.bind(x),
         Value::TimeTime(x) => query.bind(x),
-        Value::TimeOffsetDateTime(x) => query.bind(x),
+        Value::TimeOffsetDateTime(x) => query.bind(x.to_offset(UtcOffset::UTC)),
         Value::TimePrimitiveDateTime(x) => query.bind(x),
 
         Value::Uuid(x) => query.bind(x),
@@ -55,7 +88,7 @@ where
             NullType::F64 => query.bind(None::<f64>),
             NullType::F32 => query.bind(None::<f32>),
             NullType::Binary => query.bind(None::<&[u8]>),
-            NullType::Choice => {}
+            NullType::Choice => query.bind(None::<Choice>),
 
             NullType::ChronoNaiveTime => query.bind(none(Value::ChronoNaiveTime)),
             NullType::ChronoNaiveDate => query.bind(none(Value::ChronoNaiveDate)),
@@ -81,6 +114,7 @@ where
             NullType::BitVec => query.bind(none(Value::BitVec)),
         },
     }
+    Ok(())
 }
 
 /// Little helper hack to avoid using naming the types
@@ -91,3 +125,158 @@ where
 {
     None
 }
+
+/// Round trips every common [`Value`] variant through [`bind_param`] for each enabled driver
+///
+/// Postgres and MySQL are only tested when explicitly requested using `cargo test -- --ignored`.
+/// They connect to the database `rorm` on localhost as user `rorm` with password `rorm`,
+/// which can be overwritten using the environment variables
+/// `RORM_TEST_HOST`, `RORM_TEST_NAME`, `RORM_TEST_USER` and `RORM_TEST_PASSWORD`.
+#[cfg(all(test, feature = "tokio"))]
+mod test {
+    use std::fmt::Debug;
+
+    use rorm_sql::value::{NullType, Value};
+    use rorm_sql::DBImpl;
+    use sqlx::types::chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+    use sqlx::types::time::{Date, PrimitiveDateTime, Time};
+    use sqlx::types::{Json, JsonValue, Uuid};
+
+    use crate::choice::Choice;
+    use crate::database::placeholder;
+    use crate::executor::{All, Executor, Nothing};
+    use crate::row::DecodeOwned;
+    use crate::Database;
+
+    /// Column types to store a value in as `[sqlite, postgres, mysql]`
+    type ColumnTypes = [&'static str; 3];
+
+    /// Insert `value` and a `NULL` into a temporary table and check that they are read back unchanged
+    async fn assert_round_trip<T>(
+        db: &Database,
+        column_types: ColumnTypes,
+        value: Value<'_>,
+        null_type: NullType,
+        expected: T,
+    ) where
+        T: DecodeOwned + PartialEq + Debug,
+        Option<T>: DecodeOwned,
+    {
+        let dialect = db.dialect();
+        let column_type = match dialect {
+            #[cfg(feature = "sqlite")]
+            DBImpl::SQLite => column_types[0],
+            #[cfg(feature = "postgres")]
+            DBImpl::Postgres => column_types[1],
+            #[cfg(feature = "mysql")]
+            DBImpl::MySQL => column_types[2],
+        };
+        let context = format!("{value:?} as {column_type}");
+
+        db.execute::<Nothing>(
+            format!("CREATE TEMPORARY TABLE bind_param (value {column_type});"),
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+        db.execute::<Nothing>(
+            format!(
+                "INSERT INTO bind_param (value) VALUES ({}), ({});",
+                placeholder(dialect, 1),
+                placeholder(dialect, 2)
+            ),
+            vec![value, Value::Null(null_type)],
+        )
+        .await
+        .unwrap_or_else(|error| panic!("{context}: {error}"));
+        let rows = db
+            .execute::<All>(
+                String::from("SELECT value FROM bind_param ORDER BY value IS NULL;"),
+                Vec::new(),
+            )
+            .await
+            .unwrap();
+        db.execute::<Nothing>(String::from("DROP TABLE bind_param;"), Vec::new())
+            .await
+            .unwrap();
+
+        assert_eq!(rows.len(), 2, "{context}");
+        assert_eq!(
+            rows[0]
+                .get::<T, _>(0)
+                .unwrap_or_else(|error| panic!("{context}: {error}")),
+            expected,
+            "{context}"
+        );
+        assert_eq!(
+            rows[1]
+                .get::<Option<T>, _>(0)
+                .unwrap_or_else(|error| panic!("{context}: {error}")),
+            None,
+            "{context}"
+        );
+    }
+
+    async fn test_round_trips(db: Database) {
+        let date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        let time = NaiveTime::from_hms_opt(13, 37, 42).unwrap();
+        let date_time = NaiveDateTime::new(date, time);
+        let time_date = Date::from_ordinal_date(2024, 60).unwrap();
+        let time_time = Time::from_hms(13, 37, 42).unwrap();
+        let time_date_time = PrimitiveDateTime::new(time_date, time_time);
+        let uuid = Uuid::from_u128(0x0123_4567_89ab_cdef_0123_4567_89ab_cdef);
+        let json = JsonValue::from(vec![1, 2, 3]);
+
+        #[rustfmt::skip]
+        macro_rules! assert_round_trips {
+            ($($types:expr, $value:expr, $null_type:ident, $expected:expr;)*) => {$(
+                assert_round_trip(&db, $types, $value, NullType::$null_type, $expected).await;
+            )*};
+        }
+        assert_round_trips!(
+            ["TEXT", "TEXT", "TEXT"], Value::String("foo"), String, String::from("foo");
+            ["INTEGER", "BIGINT", "BIGINT"], Value::I64(i64::MIN), I64, i64::MIN;
+            ["INTEGER", "INTEGER", "INT"], Value::I32(i32::MAX), I32, i32::MAX;
+            ["INTEGER", "SMALLINT", "SMALLINT"], Value::I16(-42), I16, -42i16;
+            ["BOOLEAN", "BOOLEAN", "BOOLEAN"], Value::Bool(true), Bool, true;
+            ["REAL", "REAL", "FLOAT"], Value::F32(1.5), F32, 1.5f32;
+            ["REAL", "DOUBLE PRECISION", "DOUBLE"], Value::F64(-0.25), F64, -0.25f64;
+            ["BLOB", "BYTEA", "BLOB"], Value::Binary(&[0, 1, 255]), Binary, vec![0u8, 1, 255];
+            ["TEXT", "DATE", "DATE"], Value::ChronoNaiveDate(date), ChronoNaiveDate, date;
+            ["TEXT", "TIME", "TIME"], Value::ChronoNaiveTime(time), ChronoNaiveTime, time;
+            ["TEXT", "TIMESTAMP", "DATETIME"], Value::ChronoNaiveDateTime(date_time), ChronoNaiveDateTime, date_time;
+            ["TEXT", "TIMESTAMPTZ", "DATETIME"], Value::ChronoDateTime(Utc.from_utc_datetime(&date_time)), ChronoDateTime, Utc.from_utc_datetime(&date_time);
+            ["TEXT", "DATE", "DATE"], Value::TimeDate(time_date), TimeDate, time_date;
+            ["TEXT", "TIME", "TIME"], Value::TimeTime(time_time), TimeTime, time_time;
+            ["TEXT", "TIMESTAMP", "DATETIME"], Value::TimePrimitiveDateTime(time_date_time), TimePrimitiveDateTime, time_date_time;
+            ["TEXT", "TIMESTAMPTZ", "DATETIME"], Value::TimeOffsetDateTime(time_date_time.assume_utc()), TimeOffsetDateTime, time_date_time.assume_utc();
+            ["BLOB", "UUID", "BINARY(16)"], Value::Uuid(uuid), Uuid, uuid;
+            ["TEXT", "TEXT", "CHAR(36)"], Value::UuidHyphenated(uuid), UuidHyphenated, uuid.hyphenated().to_string();
+            ["TEXT", "TEXT", "CHAR(32)"], Value::UuidSimple(uuid), UuidSimple, uuid.simple().to_string();
+            ["TEXT", "JSONB", "JSON"], Value::JsonValue(&json), JsonValue, Json(json.clone());
+            ["TEXT", "TEXT", "VARCHAR(255)"], Value::Choice("Red"), Choice, Choice(String::from("Red"));
+        );
+
+        db.close().await;
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_bind_param_sqlite() {
+        test_round_trips(crate::internal::connect_sqlite().await).await;
+    }
+
+    #[cfg(feature = "postgres")]
+    #[tokio::test]
+    #[ignore = "requires a postgres server"]
+    async fn test_bind_param_postgres() {
+        test_round_trips(crate::internal::connect_postgres().await).await;
+    }
+
+    #[cfg(feature = "mysql")]
+    #[tokio::test]
+    #[ignore = "requires a mysql server"]
+    async fn test_bind_param_mysql() {
+        test_round_trips(crate::internal::connect_mysql().await).await;
+    }
+}
@@ -1,5 +1,21 @@
 //! This module holds the definition of transactions
 
+use std::future::ready;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use log::warn;
+use rorm_sql::value::Value;
+use rorm_sql::DBImpl;
+
+use crate::database::DatabaseSettings;
+#[cfg(feature = "postgres")]
+use crate::executor::All;
+use crate::executor::{
+    quote_identifier, DryRunExecutor, DynamicExecutor, Executor, Nothing, One, QueryStrategy,
+};
+#[cfg(feature = "postgres")]
+use crate::Row;
 use crate::{internal, Error};
 
 /**
@@ -8,13 +24,72 @@ after another with a way to go back to the start without something changed in th
 database.
 
 Can be obtained using [`Database::start_transaction`](crate::Database::start_transaction).
+
+# Executor
+Only `&mut Transaction` implements [`Executor`](crate::executor::Executor).
+A query's result (future or stream) borrows the transaction's connection until it is consumed,
+so the transaction can't be moved into the query and still be committed afterwards.
+To hand a transaction over to generic code which should commit it, use [`Transaction::run`].
+
+There is no `impl Executor for &Transaction`, even for read-only queries:
+a transaction is bound to a single connection and every driver processes
+one statement on a connection at a time.
+A second query can only be sent after the previous one's results have been fully consumed,
+so a shared borrow would just move this serialization behind a lock
+while hiding that concurrent reads inside a transaction don't run concurrently.
+To read concurrently, use separate connections i.e. `&Database`
+(which won't see the transaction's uncommitted changes).
  */
 #[must_use = "A transaction needs to be committed."]
-pub struct Transaction(pub(crate) internal::transaction::Impl);
+pub struct Transaction(
+    pub(crate) internal::transaction::Impl,
+    pub(crate) Nesting,
+    pub(crate) DatabaseSettings,
+    pub(crate) TransactionState,
+);
+
+/// Bookkeeping of a [`Transaction`]'s savepoints
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Nesting {
+    /// Number of currently open savepoints
+    pub(crate) depth: u32,
+
+    /// See [`DatabaseConfiguration::max_transaction_depth`](crate::DatabaseConfiguration::max_transaction_depth)
+    pub(crate) max_depth: Option<u32>,
+}
+
+impl Nesting {
+    /// Bookkeeping for a transaction without any savepoints
+    pub(crate) fn new(max_depth: Option<u32>) -> Self {
+        Self {
+            depth: 0,
+            max_depth,
+        }
+    }
+}
+
+/// Bookkeeping of the statements executed on a [`Transaction`]
+#[derive(Copy, Clone, Debug, Default)]
+pub(crate) struct TransactionState {
+    /// Whether any statement has been executed, see [`Transaction::is_dirty`]
+    pub(crate) dirty: bool,
+
+    /// MySQL's statement timeout in milliseconds, see [`Transaction::set_statement_timeout`]
+    pub(crate) mysql_timeout_ms: Option<u128>,
+}
 
 impl Transaction {
     /// This function commits the transaction.
+    ///
+    /// If no statement has been executed (see [`Transaction::is_dirty`]),
+    /// there is nothing to commit and the round-trip is skipped.
+    /// The transaction is ended in the background when its connection is used next.
     pub async fn commit(self) -> Result<(), Error> {
+        if !self.3.dirty {
+            // Dropping the transaction queues its rollback which is indistinguishable from a commit
+            drop(self);
+            return Ok(());
+        }
         internal::transaction::commit(self).await
     }
 
@@ -22,12 +97,345 @@ impl Transaction {
     pub async fn rollback(self) -> Result<(), Error> {
         internal::transaction::rollback(self).await
     }
+
+    /// Starts a nested transaction by creating a savepoint.
+    ///
+    /// Committing the returned [`Savepoint`] merges its changes into this transaction,
+    /// rolling it back only reverts the changes made since it was created.
+    ///
+    /// Fails with an [`Error::ConfigurationError`] if the
+    /// [`max_transaction_depth`](crate::DatabaseConfiguration::max_transaction_depth)
+    /// would be exceeded.
+    pub async fn start_transaction(&mut self) -> Result<Savepoint<'_>, Error> {
+        if let Some(max_depth) = self.1.max_depth {
+            if self.1.depth >= max_depth {
+                return Err(Error::ConfigurationError(format!(
+                    "transactions can't be nested deeper than {max_depth} savepoints"
+                )));
+            }
+        }
+
+        internal::transaction::begin_savepoint(self).await?;
+        self.1.depth += 1;
+        Ok(Savepoint {
+            transaction: self,
+            open: true,
+        })
+    }
+
+    /// Returns the number of currently open savepoints
+    ///
+    /// A transaction without any nested [`start_transaction`](Transaction::start_transaction) has a depth of 0.
+    pub fn depth(&self) -> u32 {
+        self.1.depth
+    }
+
+    /// Returns whether any statement has been executed on this transaction
+    ///
+    /// Every call to [`Executor::execute`] counts, even if the statement failed or didn't change anything.
+    /// Statements executed through a [`Savepoint`] count as well.
+    pub fn is_dirty(&self) -> bool {
+        self.3.dirty
+    }
+
+    /// Sets a timeout for the following statements which is enforced by the database server
+    ///
+    /// Unlike a timeout on the client side (e.g. by wrapping the future),
+    /// the server actually cancels a statement exceeding it and the statement fails with an error.
+    ///
+    /// - Postgres applies it until the transaction ends (`SET LOCAL statement_timeout`).
+    /// - MySQL only supports it for `SELECT` statements.
+    ///   Instead of setting `max_execution_time` for the whole session,
+    ///   which would outlive the transaction on the pooled connection,
+    ///   the `MAX_EXECUTION_TIME` optimizer hint is added to every following `SELECT` on the transaction.
+    /// - SQLite doesn't support server side timeouts and ignores it.
+    ///
+    /// The timeout has a resolution of milliseconds, a timeout of zero disables it.
+    pub async fn set_statement_timeout(&mut self, timeout: Duration) -> Result<(), Error> {
+        let mut millis = timeout.as_millis();
+        if millis == 0 && !timeout.is_zero() {
+            // Don't disable the timeout by rounding down
+            millis = 1;
+        }
+
+        let query = match self.dialect() {
+            #[cfg(feature = "postgres")]
+            DBImpl::Postgres => Some(format!("SET LOCAL statement_timeout = {millis}")),
+            #[cfg(feature = "mysql")]
+            DBImpl::MySQL => {
+                self.3.mysql_timeout_ms = (millis != 0).then_some(millis);
+                None
+            }
+            #[cfg(feature = "sqlite")]
+            DBImpl::SQLite => None,
+        };
+        match query {
+            Some(query) => self.execute::<Nothing>(query, Vec::new()).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Adds MySQL's `MAX_EXECUTION_TIME` optimizer hint to a `SELECT` statement
+    ///
+    /// See [`Transaction::set_statement_timeout`]
+    pub(crate) fn add_execution_time_hint(&self, query: String) -> String {
+        let Some(millis) = self.3.mysql_timeout_ms else {
+            return query;
+        };
+        let statement = query.trim_start();
+        let is_select = statement.get(..6).is_some_and(|keyword| {
+            keyword.eq_ignore_ascii_case("SELECT")
+                && !statement[6..].starts_with(|c: char| c.is_alphanumeric() || c == '_')
+        });
+        if !is_select {
+            return query;
+        }
+        // The hint has to follow the `SELECT` keyword directly
+        format!(
+            "SELECT /*+ MAX_EXECUTION_TIME({millis}) */{}",
+            &statement[6..]
+        )
+    }
+
+    /// Checks that the transaction's connection uses the database `name`
+    ///
+    /// This never switches databases:
+    /// a `USE` (or anything alike) on a pooled connection outlives the transaction
+    /// and would silently redirect the queries of whoever acquires the connection next.
+    /// Instead, connect a separate [`Database`](crate::Database) for every database of a server
+    /// and pick the handle to start the transaction on.
+    ///
+    /// Returns `Ok(())` if the connection already uses `name`
+    /// and an [`Error::ConfigurationError`] otherwise.
+    /// SQLite's connections always use the database `main`.
+    pub async fn use_database(&mut self, name: &str) -> Result<(), Error> {
+        let query = match self.dialect() {
+            #[cfg(feature = "postgres")]
+            DBImpl::Postgres => "SELECT current_database()",
+            #[cfg(feature = "mysql")]
+            DBImpl::MySQL => "SELECT DATABASE()",
+            #[cfg(feature = "sqlite")]
+            DBImpl::SQLite => "SELECT 'main'",
+        };
+        let row = self.execute::<One>(query.to_string(), Vec::new()).await?;
+        let current: Option<String> = row.get(0)?;
+        if current.as_deref() == Some(name) {
+            Ok(())
+        } else {
+            Err(Error::ConfigurationError(format!(
+                "the transaction uses the database {current:?} instead of {name:?}, \
+                connect a separate Database to use another database"
+            )))
+        }
+    }
+
+    /// Creates a temporary table which only exists on this transaction's connection
+    ///
+    /// Since every statement executed on the transaction uses the same connection,
+    /// the table can be filled (e.g. using [`insert_bulk`](crate::database::insert_bulk) with `&mut self` as executor)
+    /// and joined against without being visible to anyone else.
+    ///
+    /// - Postgres drops the table when the transaction ends (`ON COMMIT DROP`).
+    /// - MySQL and SQLite keep the table until the connection is closed,
+    ///   i.e. the pooled connection keeps it after the transaction ended.
+    ///   Drop it using `DROP TABLE` before committing if the connection might create it again.
+    ///
+    /// **Parameter**:
+    /// - `name`: Name of the table
+    /// - `columns`: Names and sql types of the table's columns
+    #[cfg_attr(not(feature = "postgres"), allow(unused_mut))]
+    pub async fn create_temp_table(
+        &mut self,
+        name: &str,
+        columns: &[(&str, &str)],
+    ) -> Result<(), Error> {
+        let dialect = self.dialect();
+        let columns: Vec<_> = columns
+            .iter()
+            .map(|(column, sql_type)| format!("{} {sql_type}", quote_identifier(dialect, column)))
+            .collect();
+        let mut query = format!(
+            "CREATE TEMPORARY TABLE {} ({})",
+            quote_identifier(dialect, name),
+            columns.join(", ")
+        );
+        #[cfg(feature = "postgres")]
+        if matches!(dialect, DBImpl::Postgres) {
+            query.push_str(" ON COMMIT DROP");
+        }
+        self.execute::<Nothing>(query, Vec::new()).await
+    }
+
+    /// Runs `f` on the transaction and commits it if `f` succeeded.
+    ///
+    /// If `f` returns an error, the transaction is rolled back and `f`'s error is returned.
+    ///
+    /// ```skipped
+    /// let id = tr.run(|tr| Box::pin(async move {
+    ///     let row = tr.execute::<One>(query, values).await?;
+    ///     row.get::<i64, _>(0)
+    /// })).await?;
+    /// ```
+    pub async fn run<T, F>(mut self, f: F) -> Result<T, Error>
+    where
+        F: for<'tr> FnOnce(&'tr mut Transaction) -> BoxFuture<'tr, Result<T, Error>>,
+    {
+        let result = f(&mut self).await;
+        match result {
+            Ok(value) => {
+                self.commit().await?;
+                Ok(value)
+            }
+            Err(error) => {
+                if let Err(rollback_error) = self.rollback().await {
+                    warn!("Failed to rollback transaction after an error: {rollback_error}");
+                }
+                Err(error)
+            }
+        }
+    }
+}
+
+/// A savepoint inside a [`Transaction`] i.e. a nested transaction
+///
+/// Can be obtained using [`Transaction::start_transaction`].
+///
+/// `&mut Savepoint` implements [`Executor`] by executing on its transaction.
+/// Dropping a savepoint without calling [`Savepoint::commit`] rolls it back.
+#[must_use = "A savepoint needs to be committed."]
+pub struct Savepoint<'tr> {
+    transaction: &'tr mut Transaction,
+    open: bool,
+}
+
+impl Savepoint<'_> {
+    /// Releases the savepoint, keeping its changes in the surrounding transaction.
+    ///
+    /// If releasing fails or the returned future is dropped before it completes,
+    /// the savepoint stays open and is rolled back like a dropped savepoint.
+    pub async fn commit(mut self) -> Result<(), Error> {
+        internal::transaction::commit_savepoint(&mut *self.transaction).await?;
+        self.open = false;
+        self.transaction.1.depth -= 1;
+        Ok(())
+    }
+
+    /// Rolls back all changes made since the savepoint was created.
+    pub async fn rollback(mut self) -> Result<(), Error> {
+        internal::transaction::rollback_savepoint(&mut *self.transaction).await?;
+        self.open = false;
+        self.transaction.1.depth -= 1;
+        Ok(())
+    }
+
+    /// Returns the number of currently open savepoints including this one
+    ///
+    /// See [`Transaction::depth`]
+    pub fn depth(&self) -> u32 {
+        self.transaction.depth()
+    }
+
+    /// Starts a further nested transaction
+    ///
+    /// See [`Transaction::start_transaction`]
+    pub async fn start_transaction(&mut self) -> Result<Savepoint<'_>, Error> {
+        self.transaction.start_transaction().await
+    }
+}
+
+impl Drop for Savepoint<'_> {
+    /// Queues the rollback of a savepoint which hasn't been committed or rolled back
+    fn drop(&mut self) {
+        if self.open {
+            self.transaction.1.depth -= 1;
+            internal::transaction::start_rollback_savepoint(&mut *self.transaction);
+        }
+    }
+}
+
+impl<'executor, 'tr: 'executor> Executor<'executor> for &'executor mut Savepoint<'tr> {
+    fn execute<'data, 'result, Q>(
+        self,
+        query: String,
+        values: Vec<Value<'data>>,
+    ) -> Q::Result<'result>
+    where
+        'executor: 'result,
+        'data: 'result,
+        Q: QueryStrategy,
+    {
+        self.transaction.execute::<Q>(query, values)
+    }
+
+    fn dialect(&self) -> DBImpl {
+        self.transaction.dialect()
+    }
+
+    fn into_dyn(self) -> DynamicExecutor<'executor> {
+        DynamicExecutor::Transaction(&mut *self.transaction)
+    }
+
+    type EnsureTransactionFuture = std::future::Ready<Result<TransactionGuard<'executor>, Error>>;
+
+    fn ensure_transaction(
+        self,
+    ) -> BoxFuture<'executor, Result<TransactionGuard<'executor>, Error>> {
+        Box::pin(ready(Ok(TransactionGuard::Borrowed(
+            &mut *self.transaction,
+        ))))
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl Transaction {
+    /// Declares a server-side cursor called `name` which iterates over the rows returned by `query`.
+    ///
+    /// Retrieve its rows using [`Transaction::fetch_cursor`].
+    /// The cursor is closed automatically when the transaction ends.
+    ///
+    /// Cursors are only supported on postgres,
+    /// other databases produce an [`Error::ConfigurationError`].
+    pub async fn declare_cursor(
+        &mut self,
+        query: &str,
+        values: Vec<Value<'_>>,
+        name: &str,
+    ) -> Result<(), Error> {
+        if !matches!(self.dialect(), DBImpl::Postgres) {
+            return Err(Error::ConfigurationError(String::from(
+                "cursors are only supported on postgres",
+            )));
+        }
+
+        let query = format!(
+            "DECLARE {} CURSOR FOR {}",
+            quote_cursor(name),
+            query.trim_end().trim_end_matches(';')
+        );
+        self.execute::<Nothing>(query, values).await
+    }
+
+    /// Fetches the next `count` rows from a cursor declared with [`Transaction::declare_cursor`].
+    ///
+    /// **Returns** an empty vector once the cursor is exhausted.
+    pub async fn fetch_cursor(&mut self, name: &str, count: u64) -> Result<Vec<Row>, Error> {
+        let query = format!("FETCH {count} FROM {}", quote_cursor(name));
+        self.execute::<All>(query, Vec::new()).await
+    }
+}
+
+/// Quote a cursor's name as postgres identifier
+#[cfg(feature = "postgres")]
+fn quote_cursor(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
 }
 
 /// Either an owned or borrowed [`Transaction`].
 ///
 /// "Guarding" a piece of code which has to be run in an transaction
 /// (see [`Executor::ensure_transaction`](crate::executor::Executor::ensure_transaction))
+///
+/// `&mut TransactionGuard` implements [`Executor`] to run the guarded code on the transaction.
 #[must_use = "The potentially owned transaction needs to be committed."]
 pub enum TransactionGuard<'tr> {
     /// An owned transaction
@@ -35,14 +443,30 @@ pub enum TransactionGuard<'tr> {
 
     /// A borrowed transaction
     Borrowed(&'tr mut Transaction),
+
+    /// A dry run which captures the guarded code's queries instead of running them in a transaction
+    DryRun(&'tr DryRunExecutor),
 }
 
 impl TransactionGuard<'_> {
     /// Get a reference to the guarded transaction
+    ///
+    /// # Panics
+    /// If called on a [`TransactionGuard::DryRun`] which doesn't have a transaction.
+    /// Use [`TransactionGuard::try_get_transaction`] if the guard might come from a dry run.
     pub fn get_transaction(&mut self) -> &mut Transaction {
+        self.try_get_transaction()
+            .expect("a dry run's TransactionGuard doesn't have a transaction")
+    }
+
+    /// Get a reference to the guarded transaction
+    ///
+    /// **Returns** `None` for a [`TransactionGuard::DryRun`] which doesn't have one.
+    pub fn try_get_transaction(&mut self) -> Option<&mut Transaction> {
         match self {
-            TransactionGuard::Owned(tr) => tr,
-            TransactionGuard::Borrowed(tr) => tr,
+            TransactionGuard::Owned(tr) => Some(tr),
+            TransactionGuard::Borrowed(tr) => Some(tr),
+            TransactionGuard::DryRun(_) => None,
         }
     }
 
@@ -55,3 +479,109 @@ impl TransactionGuard<'_> {
         }
     }
 }
+
+impl<'executor, 'tr: 'executor> Executor<'executor> for &'executor mut TransactionGuard<'tr> {
+    fn execute<'data, 'result, Q>(
+        self,
+        query: String,
+        values: Vec<Value<'data>>,
+    ) -> Q::Result<'result>
+    where
+        'executor: 'result,
+        'data: 'result,
+        Q: QueryStrategy,
+    {
+        self.into_dyn().execute::<Q>(query, values)
+    }
+
+    fn dialect(&self) -> DBImpl {
+        match self {
+            TransactionGuard::Owned(tr) => internal::transaction::dialect(tr),
+            TransactionGuard::Borrowed(tr) => internal::transaction::dialect(tr),
+            TransactionGuard::DryRun(dry_run) => dry_run.dialect(),
+        }
+    }
+
+    fn into_dyn(self) -> DynamicExecutor<'executor> {
+        match self {
+            TransactionGuard::Owned(tr) => DynamicExecutor::Transaction(tr),
+            TransactionGuard::Borrowed(tr) => DynamicExecutor::Transaction(tr),
+            TransactionGuard::DryRun(dry_run) => DynamicExecutor::DryRun(dry_run),
+        }
+    }
+
+    type EnsureTransactionFuture = std::future::Ready<Result<TransactionGuard<'executor>, Error>>;
+
+    fn ensure_transaction(
+        self,
+    ) -> BoxFuture<'executor, Result<TransactionGuard<'executor>, Error>> {
+        Box::pin(ready(Ok(match self {
+            TransactionGuard::Owned(tr) => TransactionGuard::Borrowed(tr),
+            TransactionGuard::Borrowed(tr) => TransactionGuard::Borrowed(tr),
+            TransactionGuard::DryRun(dry_run) => TransactionGuard::DryRun(dry_run),
+        })))
+    }
+}
+
+#[cfg(all(test, feature = "sqlite", feature = "tokio"))]
+mod test {
+    use rorm_sql::value::Value;
+
+    use crate::database::insert_bulk;
+    use crate::executor::{All, Executor, Nothing};
+    use crate::internal::connect_sqlite;
+
+    #[tokio::test]
+    async fn test_temp_table_sqlite() {
+        let db = connect_sqlite().await;
+        db.execute::<Nothing>(
+            String::from("CREATE TABLE user (id INTEGER PRIMARY KEY, name TEXT NOT NULL);"),
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+        db.execute::<Nothing>(
+            String::from("INSERT INTO user (id, name) VALUES (1, 'alice'), (2, 'bob');"),
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+
+        let mut tr = db.start_transaction().await.unwrap();
+        tr.create_temp_table("staging", &[("user_id", "INTEGER"), ("score", "INTEGER")])
+            .await
+            .unwrap();
+        insert_bulk(
+            &mut tr,
+            "staging",
+            &["user_id", "score"],
+            &[
+                &[Value::I64(2), Value::I64(20)],
+                &[Value::I64(1), Value::I64(10)],
+            ],
+        )
+        .await
+        .unwrap();
+        let rows = tr
+            .execute::<All>(
+                String::from(
+                    "SELECT user.name, staging.score FROM user \
+                    JOIN staging ON user.id = staging.user_id ORDER BY user.id;",
+                ),
+                Vec::new(),
+            )
+            .await
+            .unwrap();
+        let rows: Vec<(String, i64)> = rows
+            .iter()
+            .map(|row| (row.get(0).unwrap(), row.get(1).unwrap()))
+            .collect();
+        assert_eq!(
+            rows,
+            [(String::from("alice"), 10), (String::from("bob"), 20)]
+        );
+        tr.rollback().await.unwrap();
+
+        db.close().await;
+    }
+}
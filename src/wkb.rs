@@ -0,0 +1,68 @@
+//! Raw geometries for PostGIS' `geometry` extension type
+
+/// Geometry stored in PostGIS' `geometry` extension type as raw (E)WKB bytes
+///
+/// Read it using [`Row::get`](crate::Row::get)
+/// or without any conversion using [`Row::get_raw`](crate::Row::get_raw).
+/// It can't be bound yet, because rorm-sql's `Value` has no variant for it.
+///
+/// The bytes are neither parsed nor validated, PostGIS rejects malformed geometries.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Wkb(pub Vec<u8>);
+
+const _: () = {
+    use sqlx::error::BoxDynError;
+    use sqlx::postgres::{PgTypeInfo, PgValueFormat, PgValueRef};
+    use sqlx::{Decode, Postgres, Type};
+
+    impl Type<Postgres> for Wkb {
+        fn type_info() -> PgTypeInfo {
+            // Since `geometry` is enabled by an extension, it does not have a stable OID.
+            PgTypeInfo::with_name("geometry")
+        }
+    }
+
+    impl<'r> Decode<'r, Postgres> for Wkb {
+        fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+            match value.format() {
+                PgValueFormat::Binary => Ok(Self(value.as_bytes()?.to_vec())),
+                PgValueFormat::Text => decode_hex(value.as_str()?).map(Self),
+            }
+        }
+    }
+};
+
+/// Decode the format produced by PostGIS' `geometry_out` i.e. hex encoded EWKB
+fn decode_hex(input: &str) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+    fn digit(byte: u8) -> Result<u8, Box<dyn std::error::Error + Send + Sync>> {
+        match (byte as char).to_digit(16) {
+            Some(digit) => Ok(digit as u8),
+            None => Err("invalid hex digit in geometry".into()),
+        }
+    }
+
+    input
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| match *pair {
+            [high, low] => Ok((digit(high)? << 4) | digit(low)?),
+            _ => Err("odd number of hex digits in geometry".into()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::decode_hex;
+
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(
+            decode_hex("0101000000000000000000F03F").unwrap(),
+            [1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xF0, 0x3F]
+        );
+        assert!(decode_hex("").unwrap().is_empty());
+        assert!(decode_hex("010").is_err());
+        assert!(decode_hex("0G").is_err());
+    }
+}